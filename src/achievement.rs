@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+
+use bevy::{
+    asset::{Asset, Assets, Handle},
+    ecs::system::SystemParam,
+    prelude::{
+        App, Changed, Commands, DirectAssetAccessExt, Event, FromWorld, IntoSystemConfigs, Plugin,
+        Query, Res, ResMut, Resource, Trigger, TypePath, Update, World,
+    },
+};
+
+use crate::{
+    Ascension, Campaign, CampaignSystems, Endless, Game, GameEnded, GameOver, Phase, PlayerClass,
+    PlayerElements, Profile, RonAssetPlugin, Round, RoundHistory, RoundResolved,
+};
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<AchievementList>::new(&[
+            "achievements.ron",
+        ]));
+        app.init_resource::<AchievementListHandle>();
+        app.add_observer(Self::check_rounds);
+        app.add_observer(Self::check_games);
+        app.add_systems(Update, Self::check_campaigns.after(CampaignSystems));
+    }
+}
+
+impl AchievementsPlugin {
+    fn check_rounds(
+        trigger: Trigger<RoundResolved>,
+        mut commands: Commands,
+        games: Query<(&Game, &RoundHistory)>,
+        campaigns: Query<&Campaign>,
+        achievements: CurrentAchievements,
+        mut profile: ResMut<Profile>,
+    ) {
+        let RoundResolved { game, round } = trigger.event();
+        let Ok((game, history)) = games.get(*game) else {
+            return;
+        };
+        if round.replayed {
+            return;
+        }
+        for Campaign { player } in &campaigns {
+            let Some(seat) = game.seat(*player) else {
+                continue;
+            };
+            let combo_streak = history.combo_streak(seat);
+            if combo_streak > 0 {
+                achievements.unlock_matching(
+                    &AchievementTrigger::ComboStreak(combo_streak as u32),
+                    &mut profile,
+                    &mut commands,
+                );
+            }
+        }
+    }
+
+    fn check_games(
+        trigger: Trigger<GameEnded>,
+        mut commands: Commands,
+        games: Query<(&Game, &RoundHistory)>,
+        campaigns: Query<&Campaign>,
+        achievements: CurrentAchievements,
+        mut profile: ResMut<Profile>,
+    ) {
+        let GameEnded { game, result } = *trigger.event();
+        let GameOver::Winner(winner) = result else {
+            return;
+        };
+        if !campaigns.iter().any(|campaign| campaign.player == winner) {
+            return;
+        }
+        let Ok((game, history)) = games.get(game) else {
+            return;
+        };
+        let Some(seat) = game.seat(winner) else {
+            return;
+        };
+        achievements.unlock_matching(
+            &AchievementTrigger::GameWon {
+                damage_taken: history.damage_taken(seat),
+                staggered_opponent: history.was_staggered(seat.opponent()),
+            },
+            &mut profile,
+            &mut commands,
+        );
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn check_campaigns(
+        mut commands: Commands,
+        campaigns: Query<
+            (
+                &Campaign,
+                &Phase,
+                &Round,
+                &Ascension,
+                &PlayerClass,
+                Option<&Endless>,
+            ),
+            Changed<Phase>,
+        >,
+        players: Query<&PlayerElements>,
+        achievements: CurrentAchievements,
+        mut profile: ResMut<Profile>,
+    ) {
+        for (Campaign { player }, phase, round, ascension, player_class, endless) in &campaigns {
+            let trigger = match (phase, endless) {
+                (Phase::Victory, _) => AchievementTrigger::CampaignWon {
+                    num_elements: players
+                        .get(*player)
+                        .map_or(0, |elements| elements.elements.len()),
+                    ascension: *ascension,
+                    player_class: *player_class,
+                },
+                (Phase::ProvidingBonus, Some(endless)) => {
+                    AchievementTrigger::EndlessDepth(endless.depth(*round))
+                }
+                _ => continue,
+            };
+            achievements.unlock_matching(&trigger, &mut profile, &mut commands);
+        }
+    }
+}
+
+/// Something notable which happened to the campaign player.
+#[derive(Clone, Debug)]
+pub enum AchievementTrigger {
+    GameWon {
+        damage_taken: i32,
+        staggered_opponent: bool,
+    },
+    ComboStreak(u32),
+    CampaignWon {
+        num_elements: usize,
+        ascension: Ascension,
+        player_class: PlayerClass,
+    },
+    EndlessDepth(u16),
+}
+
+/// What must happen for an achievement to unlock.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum AchievementCondition {
+    /// Win a game without taking any damage.
+    FlawlessGame,
+    /// Win a game after staggering the opponent with Parry.
+    ParryWin,
+    /// Trigger doubled Combo damage this many rounds in a row.
+    ComboStreak(u32),
+    /// Win a campaign, optionally with restrictions.
+    CampaignWin {
+        #[serde(default)]
+        max_elements: Option<usize>,
+        #[serde(default)]
+        min_ascension: Option<u8>,
+        #[serde(default)]
+        player_class: Option<PlayerClass>,
+    },
+    /// Survive this many rounds in endless mode.
+    EndlessDepth(u16),
+}
+
+impl AchievementCondition {
+    pub fn is_met(&self, trigger: &AchievementTrigger) -> bool {
+        match (self, trigger) {
+            (Self::FlawlessGame, AchievementTrigger::GameWon { damage_taken, .. }) => {
+                *damage_taken == 0
+            }
+            (
+                Self::ParryWin,
+                AchievementTrigger::GameWon {
+                    staggered_opponent, ..
+                },
+            ) => *staggered_opponent,
+            (Self::ComboStreak(required), AchievementTrigger::ComboStreak(streak)) => {
+                streak >= required
+            }
+            (
+                Self::CampaignWin {
+                    max_elements,
+                    min_ascension,
+                    player_class,
+                },
+                AchievementTrigger::CampaignWon {
+                    num_elements,
+                    ascension,
+                    player_class: won_class,
+                },
+            ) => {
+                max_elements.is_none_or(|max| *num_elements <= max)
+                    && min_ascension.is_none_or(|min| **ascension >= min)
+                    && player_class.is_none_or(|class| class == *won_class)
+            }
+            (Self::EndlessDepth(required), AchievementTrigger::EndlessDepth(depth)) => {
+                depth >= required
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct Achievement {
+    /// Stable identifier saved in the profile once unlocked.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: AchievementCondition,
+}
+
+/// Every achievement which can be unlocked, loaded from a data file.
+#[derive(Clone, Debug, Default)]
+#[derive(Deserialize, Serialize)]
+#[derive(Asset, TypePath)]
+pub struct AchievementList {
+    pub achievements: Vec<Achievement>,
+}
+
+#[derive(Resource)]
+pub struct AchievementListHandle(pub Handle<AchievementList>);
+
+impl FromWorld for AchievementListHandle {
+    fn from_world(world: &mut World) -> Self {
+        AchievementListHandle(world.load_asset("data/default.achievements.ron"))
+    }
+}
+
+/// Provides the currently loaded [`AchievementList`] to systems.
+#[derive(SystemParam)]
+pub struct CurrentAchievements<'w> {
+    handle: Res<'w, AchievementListHandle>,
+    lists: Res<'w, Assets<AchievementList>>,
+}
+
+impl CurrentAchievements<'_> {
+    pub fn get(&self) -> Option<&AchievementList> {
+        self.lists.get(&self.handle.0)
+    }
+
+    /// Unlocks every achievement whose condition is met by `trigger`,
+    /// announcing each new unlock with an [`AchievementUnlocked`] event.
+    pub fn unlock_matching(
+        &self,
+        trigger: &AchievementTrigger,
+        profile: &mut Profile,
+        commands: &mut Commands,
+    ) {
+        let Some(list) = self.get() else {
+            return;
+        };
+        for achievement in &list.achievements {
+            if profile.achievements.contains(&achievement.id)
+                || !achievement.condition.is_met(trigger)
+            {
+                continue;
+            }
+            profile.achievements.insert(achievement.id.clone());
+            commands.trigger(AchievementUnlocked {
+                achievement: achievement.clone(),
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Event)]
+pub struct AchievementUnlocked {
+    pub achievement: Achievement,
+}
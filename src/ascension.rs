@@ -0,0 +1,94 @@
+use derive_more::derive::Deref;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    RngCore,
+};
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::Component;
+
+use crate::{Augmentation, Health, PlayerElements};
+
+/// A difficulty tier unlocked by winning a campaign at the tier below it.
+/// Each tier keeps the modifiers of every tier beneath it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deref)]
+#[derive(Deserialize, Serialize)]
+#[derive(Component)]
+pub struct Ascension(u8);
+
+impl Ascension {
+    pub const MAX: Self = Ascension(4);
+
+    const PLAYER_HEALTH_PENALTY: u32 = 5;
+
+    pub fn new(tier: u8) -> Self {
+        Ascension(tier.min(Self::MAX.0))
+    }
+
+    pub fn next(&self) -> Self {
+        Self::new(self.0.saturating_add(1))
+    }
+
+    pub fn previous(&self) -> Self {
+        Self::new(self.0.saturating_sub(1))
+    }
+
+    /// Describes the modifier introduced at this tier.
+    pub fn get_description(&self) -> &'static str {
+        match self.0 {
+            0 => "No modifiers",
+            1 => "You start with 5 less HP",
+            2 => "Enemies gain a free augmentation",
+            3 => "One fewer bonus is offered",
+            _ => "Draws deal 1 damage to both sides",
+        }
+    }
+
+    pub fn player_health(&self, starting_health: Health) -> Health {
+        if self.0 >= 1 {
+            Health::new(starting_health.saturating_sub(Self::PLAYER_HEALTH_PENALTY))
+        } else {
+            starting_health
+        }
+    }
+
+    /// Augments one of the enemy's unaugmented elements.
+    pub fn upgrade_enemy(&self, elements: &mut PlayerElements, rng: &mut impl RngCore) {
+        if self.0 < 2 {
+            return;
+        }
+        let element = elements
+            .elements
+            .iter()
+            .filter(|element| !elements.augmentations.contains_key(element))
+            .cloned()
+            .choose(rng);
+        let Some(element) = element else {
+            return;
+        };
+        let augmentation = *Augmentation::ALL.choose(rng).unwrap();
+        if let Err(error) = elements.augment(element, augmentation) {
+            bevy::log::warn!("Failed to augment enemy element: {error}");
+        }
+    }
+
+    /// The number of bonuses offered after a battle, out of the `offered` configured ones.
+    /// Ascension never takes away the last bonus, but a campaign may offer none at all.
+    pub fn num_bonuses(&self, offered: usize) -> usize {
+        if self.0 >= 3 && offered > 1 {
+            offered - 1
+        } else {
+            offered
+        }
+    }
+
+    /// Extra chip damage dealt to both sides when they throw the same element.
+    pub fn draw_damage(&self) -> u32 {
+        if self.0 >= 4 {
+            1
+        } else {
+            0
+        }
+    }
+}
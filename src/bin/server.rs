@@ -0,0 +1,42 @@
+//! A headless server hosting online games for clients connecting over WebSocket.
+//! Pass the address to listen on as the first argument, which is `127.0.0.1:7878` by default.
+
+use std::time::Duration;
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    log::LogPlugin,
+    prelude::{App, PluginGroup},
+    MinimalPlugins,
+};
+use bevy_prng::WyRand;
+use bevy_rand::plugin::EntropyPlugin;
+
+use rock_paper_paradise::{GamePlugin, OnlineServerPlugin, WebSocketListener};
+
+fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:7878".to_string());
+    let listener = match WebSocketListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on {address}. {error}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1. / 60.,
+        ))),
+        LogPlugin::default(),
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+        OnlineServerPlugin,
+    ));
+    app.insert_resource(listener);
+    bevy::log::info!("Listening on {address}");
+    app.run();
+}
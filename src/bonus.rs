@@ -0,0 +1,622 @@
+use derive_more::derive::{Deref, DerefMut, Display};
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    RngCore,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
+
+use bevy::{
+    asset::{Asset, Assets, Handle},
+    color::palettes::css,
+    ecs::system::SystemParam,
+    prelude::{
+        App, Color, Component, DirectAssetAccessExt, FromWorld, Plugin, Res, Resource, TypePath,
+        World,
+    },
+};
+
+use crate::{
+    Aspect, Augmentation, Element, ElementTable, ElementUpgrades, Health, Level, MaxHealth, Payout,
+    PlayerElements, Relic, RewardOffers, RonAssetPlugin, UpgradeError,
+};
+
+pub struct BonusPlugin;
+
+impl Plugin for BonusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<BonusPool>::new(&["bonuses.ron"]));
+        app.init_resource::<BonusPoolHandle>();
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum Bonus {
+    AttackPlus { enemy_element: Element },
+    DefensePlus { enemy_element: Element },
+    DoubleDown,
+    Augment(Augmentation),
+    Enchant(Aspect),
+    MaxHealthPlus,
+    Heal,
+    RemoveElement,
+    Transmute { target: Element },
+    SwapAugmentation { target: Element },
+}
+
+impl Bonus {
+    pub const MAX_HEALTH_INCREASE: u32 = 5;
+    pub const HEAL_AMOUNT: u32 = 8;
+
+    pub fn kind(&self) -> BonusKind {
+        match self {
+            Bonus::AttackPlus { .. } => BonusKind::AttackPlus,
+            Bonus::DefensePlus { .. } => BonusKind::DefensePlus,
+            Bonus::DoubleDown => BonusKind::DoubleDown,
+            Bonus::Augment(augmentation) => BonusKind::Augment(*augmentation),
+            Bonus::Enchant(_) => BonusKind::Enchant,
+            Bonus::MaxHealthPlus => BonusKind::MaxHealthPlus,
+            Bonus::Heal => BonusKind::Heal,
+            Bonus::RemoveElement => BonusKind::RemoveElement,
+            Bonus::Transmute { .. } => BonusKind::Transmute,
+            Bonus::SwapAugmentation { .. } => BonusKind::SwapAugmentation,
+        }
+    }
+
+    pub fn get_readable_name(&self) -> String {
+        match self {
+            Bonus::AttackPlus { enemy_element } => format!("Attack+ vs. {enemy_element}"),
+            Bonus::DefensePlus { enemy_element } => format!("Defense+ vs. {enemy_element}"),
+            Bonus::DoubleDown => "Double Down".to_string(),
+            Bonus::Augment(augmentation) => format!("Augmentation: {augmentation}"),
+            Bonus::Enchant(aspect) => format!("Enchantment: Aspect of {}", **aspect),
+            Bonus::MaxHealthPlus => "Max HP+".to_string(),
+            Bonus::Heal => "Heal".to_string(),
+            Bonus::RemoveElement => "Remove Element".to_string(),
+            Bonus::Transmute { target } => format!("Transmute to {target}"),
+            Bonus::SwapAugmentation { target } => format!("Move Augmentation to {target}"),
+        }
+    }
+
+    pub fn get_description(&self, element: Element) -> String {
+        match self {
+            Bonus::AttackPlus { enemy_element } => {
+                format!("{element} deals 1 additional damage against {enemy_element}")
+            }
+            Bonus::DefensePlus { enemy_element } => format!(
+                "{element} receives 1 less damage (or heals 1 more HP) against {enemy_element}"
+            ),
+            Bonus::DoubleDown => format!("Doubles all payouts for {element}."),
+            Bonus::Augment(augmentation) => augmentation.get_description(element),
+            Bonus::Enchant(aspect) => aspect.get_description(element),
+            Bonus::MaxHealthPlus => format!(
+                "Increase max HP by {0} and heal {0}.",
+                Self::MAX_HEALTH_INCREASE
+            ),
+            Bonus::Heal => format!("Heal {} HP.", Self::HEAL_AMOUNT),
+            Bonus::RemoveElement => format!(
+                "Remove {element} from your elements, along with its upgrades. You must keep at least {} elements.",
+                PlayerElements::MIN_ELEMENTS
+            ),
+            Bonus::Transmute { target } => format!(
+                "Move the augmentation and aspect of {element} onto {target}, replacing any that {target} has."
+            ),
+            Bonus::SwapAugmentation { target } => format!(
+                "Move the augmentation of {element} onto {target}. If {target} is augmented, the two augmentations are swapped."
+            ),
+        }
+    }
+
+    /// Describes the existing upgrade that applying this bonus would replace, if any.
+    pub fn conflict(
+        &self,
+        player_elements: &PlayerElements,
+        element: Element,
+    ) -> Option<UpgradeConflict> {
+        let current = player_elements.get_upgrades(element);
+        match self {
+            Bonus::Augment(augmentation) => current
+                .augmentation
+                .filter(|current| current != augmentation)
+                .map(|_| UpgradeConflict {
+                    element,
+                    new: ElementUpgrades {
+                        augmentation: Some(*augmentation),
+                        ..current.clone()
+                    },
+                    current,
+                }),
+            Bonus::Enchant(aspect) => {
+                current
+                    .aspect
+                    .filter(|current| current != aspect)
+                    .map(|_| UpgradeConflict {
+                        element,
+                        new: ElementUpgrades {
+                            aspect: Some(*aspect),
+                            ..current.clone()
+                        },
+                        current,
+                    })
+            }
+            Bonus::Transmute { target } => {
+                let target_upgrades = player_elements.get_upgrades(*target);
+                let aspect = current.aspect.filter(|aspect| **aspect != *target);
+                let replaces_augmentation =
+                    current.augmentation.is_some() && target_upgrades.augmentation.is_some();
+                let replaces_aspect = aspect.is_some() && target_upgrades.aspect.is_some();
+                if replaces_augmentation || replaces_aspect {
+                    Some(UpgradeConflict {
+                        element: *target,
+                        new: ElementUpgrades {
+                            augmentation: current.augmentation.or(target_upgrades.augmentation),
+                            aspect: aspect.or(target_upgrades.aspect),
+                        },
+                        current: target_upgrades,
+                    })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn update_game(
+        &self,
+        table: &mut ElementTable,
+        player_elements: &mut PlayerElements,
+        health: &mut Health,
+        max_health: &mut MaxHealth,
+        element_to_upgrade: Element,
+    ) -> Result<(), UpgradeError> {
+        if !player_elements.elements.contains(&element_to_upgrade) {
+            return Err(UpgradeError::MissingElement(element_to_upgrade));
+        }
+        match self {
+            Bonus::AttackPlus { enemy_element } => {
+                table.update(
+                    element_to_upgrade,
+                    *enemy_element,
+                    Payout {
+                        damage_to_enemy: 1,
+                        damage_to_me: 0,
+                    },
+                );
+            }
+            Bonus::DefensePlus { enemy_element } => {
+                table.update(
+                    element_to_upgrade,
+                    *enemy_element,
+                    Payout {
+                        damage_to_enemy: 0,
+                        damage_to_me: -1,
+                    },
+                );
+            }
+            Bonus::DoubleDown => {
+                for enemy_element in Element::ALL {
+                    table.double(element_to_upgrade, enemy_element);
+                }
+            }
+            Bonus::Augment(augmentation) => {
+                player_elements.augment(element_to_upgrade, *augmentation)?;
+            }
+            Bonus::Enchant(aspect) => {
+                player_elements.enchant(element_to_upgrade, *aspect)?;
+            }
+            Bonus::MaxHealthPlus => {
+                **max_health += Self::MAX_HEALTH_INCREASE;
+                health.heal(Self::MAX_HEALTH_INCREASE, max_health);
+            }
+            Bonus::Heal => {
+                health.heal(Self::HEAL_AMOUNT, max_health);
+            }
+            Bonus::RemoveElement => {
+                if player_elements.elements.len() <= PlayerElements::MIN_ELEMENTS {
+                    return Err(UpgradeError::TooFewElements);
+                }
+                player_elements.remove(element_to_upgrade);
+            }
+            Bonus::Transmute { target } => {
+                if !player_elements.elements.contains(target) {
+                    return Err(UpgradeError::MissingElement(*target));
+                }
+                let augmentation = player_elements.augmentations.remove(&element_to_upgrade);
+                let aspect = player_elements.enchantments.remove(&element_to_upgrade);
+                if let Some(augmentation) = augmentation {
+                    player_elements.augment(*target, augmentation)?;
+                }
+                if let Some(aspect) = aspect.filter(|aspect| **aspect != *target) {
+                    player_elements.enchant(*target, aspect)?;
+                }
+            }
+            Bonus::SwapAugmentation { target } => {
+                if !player_elements.elements.contains(target) {
+                    return Err(UpgradeError::MissingElement(*target));
+                }
+                let augmentation = player_elements.augmentations.remove(&element_to_upgrade);
+                let target_augmentation = player_elements.augmentations.remove(target);
+                if let Some(augmentation) = augmentation {
+                    player_elements.augment(*target, augmentation)?;
+                }
+                if let Some(target_augmentation) = target_augmentation {
+                    player_elements.augment(element_to_upgrade, target_augmentation)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An existing upgrade which would be replaced by applying a bonus.
+#[derive(Clone, Debug)]
+pub struct UpgradeConflict {
+    pub element: Element,
+    pub current: ElementUpgrades,
+    pub new: ElementUpgrades,
+}
+
+/// The variant of a [`Bonus`] without its randomly generated details.
+/// Bonus pools are defined in terms of kinds so that they can be described in data files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deserialize, Serialize)]
+pub enum BonusKind {
+    AttackPlus,
+    DefensePlus,
+    DoubleDown,
+    Augment(Augmentation),
+    Enchant,
+    MaxHealthPlus,
+    Heal,
+    RemoveElement,
+    Transmute,
+    SwapAugmentation,
+}
+
+impl BonusKind {
+    /// Generates a concrete bonus of this kind for `element`,
+    /// or `None` if the bonus would have no effect on the player's elements.
+    pub fn generate(
+        &self,
+        element: Element,
+        player_elements: &PlayerElements,
+        rng: &mut impl RngCore,
+    ) -> Option<Bonus> {
+        match self {
+            BonusKind::AttackPlus => Some(Bonus::AttackPlus {
+                enemy_element: Element::random(rng),
+            }),
+            BonusKind::DefensePlus => Some(Bonus::DefensePlus {
+                enemy_element: Element::random(rng),
+            }),
+            BonusKind::DoubleDown => Some(Bonus::DoubleDown),
+            BonusKind::Augment(augmentation) => {
+                if player_elements.get_augmentation(element) == Some(augmentation) {
+                    None
+                } else {
+                    Some(Bonus::Augment(*augmentation))
+                }
+            }
+            BonusKind::Enchant => {
+                let current_aspect = player_elements
+                    .get_enchantment(element)
+                    .map(|aspect| **aspect);
+                Element::ALL
+                    .into_iter()
+                    .filter(|aspect| *aspect != element && Some(*aspect) != current_aspect)
+                    .choose(rng)
+                    .map(|aspect| Bonus::Enchant(Aspect::new(aspect)))
+            }
+            BonusKind::MaxHealthPlus => Some(Bonus::MaxHealthPlus),
+            BonusKind::Heal => Some(Bonus::Heal),
+            BonusKind::RemoveElement => {
+                if player_elements.elements.len() > PlayerElements::MIN_ELEMENTS {
+                    Some(Bonus::RemoveElement)
+                } else {
+                    None
+                }
+            }
+            BonusKind::Transmute => {
+                let augmentation = player_elements.get_augmentation(element);
+                let aspect = player_elements.get_enchantment(element);
+                if augmentation.is_none() && aspect.is_none() {
+                    return None;
+                }
+                player_elements
+                    .elements
+                    .iter()
+                    .filter(|target| {
+                        **target != element && aspect.is_none_or(|aspect| **aspect != **target)
+                    })
+                    .choose(rng)
+                    .map(|target| Bonus::Transmute { target: *target })
+            }
+            BonusKind::SwapAugmentation => {
+                let augmentation = player_elements.get_augmentation(element)?;
+                player_elements
+                    .elements
+                    .iter()
+                    .filter(|target| {
+                        **target != element
+                            && player_elements.augmentations.get(target) != Some(augmentation)
+                    })
+                    .choose(rng)
+                    .map(|target| Bonus::SwapAugmentation { target: *target })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Display)]
+#[derive(Deserialize, Serialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+impl Rarity {
+    pub fn primary_color(&self) -> Color {
+        match self {
+            Rarity::Common => css::WHITE,
+            Rarity::Uncommon => css::DEEP_SKY_BLUE,
+            Rarity::Rare => css::GOLD,
+        }
+        .into()
+    }
+}
+
+/// A bonus offered to the player after a victory.
+#[derive(Clone, Debug)]
+pub struct BonusOffer {
+    pub bonus: Bonus,
+    pub element: Element,
+    pub rarity: Rarity,
+}
+
+/// The weighted table of bonuses which may be offered, loaded from `assets/data/*.bonuses.ron`.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+#[derive(Asset, TypePath)]
+pub struct BonusPool {
+    /// The number of non-rare bonuses offered in a row before a rare bonus is guaranteed.
+    pub pity_threshold: u32,
+    pub tiers: Vec<RarityTier>,
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct RarityTier {
+    pub rarity: Rarity,
+    /// The weight of this tier, starting at each level.
+    /// Levels without an entry use the weight of the closest lower level.
+    pub weights: BTreeMap<Level, u32>,
+    pub bonuses: Vec<(BonusKind, u32)>,
+}
+
+impl RarityTier {
+    pub fn weight(&self, level: Level) -> u32 {
+        self.weights
+            .range(..=level)
+            .next_back()
+            .map(|(_, weight)| *weight)
+            .unwrap_or_default()
+    }
+
+    fn roll(
+        &self,
+        element: Element,
+        player_elements: &PlayerElements,
+        banished: &BanishedBonuses,
+        rng: &mut impl RngCore,
+    ) -> Option<Bonus> {
+        let mut candidates = self
+            .bonuses
+            .iter()
+            .filter(|(kind, _)| !banished.contains(kind))
+            .cloned()
+            .collect::<Vec<_>>();
+        while let Ok(choice) = candidates.choose_weighted(rng, |(_, weight)| *weight) {
+            let kind = choice.0;
+            if let Some(bonus) = kind.generate(element, player_elements, rng) {
+                return Some(bonus);
+            }
+            candidates.retain(|(candidate, _)| *candidate != kind);
+        }
+        None
+    }
+}
+
+impl BonusPool {
+    /// Rolls a bonus for one of the player's elements, updating the bad-luck protection counter.
+    pub fn roll(
+        &self,
+        level: Level,
+        player_elements: &PlayerElements,
+        banished: &BanishedBonuses,
+        pity: &mut BonusPity,
+        rng: &mut impl RngCore,
+    ) -> Option<BonusOffer> {
+        let element = player_elements.elements.iter().cloned().choose(rng)?;
+        // tiers with no weight at this level are never offered, unless every tier is turned off
+        let is_any_tier_weighted = self.tiers.iter().any(|tier| tier.weight(level) > 0);
+        let weight = |tier: &RarityTier| {
+            if is_any_tier_weighted {
+                tier.weight(level)
+            } else {
+                1
+            }
+        };
+        let mut tiers = self
+            .tiers
+            .iter()
+            .filter(|tier| weight(tier) > 0)
+            .collect::<Vec<_>>();
+        if **pity >= self.pity_threshold && tiers.iter().any(|tier| tier.rarity == Rarity::Rare) {
+            tiers.retain(|tier| tier.rarity == Rarity::Rare);
+        }
+        while let Ok(tier) = tiers.choose_weighted(rng, |tier| weight(tier)) {
+            let tier = *tier;
+            if let Some(bonus) = tier.roll(element, player_elements, banished, rng) {
+                if tier.rarity == Rarity::Rare {
+                    **pity = 0;
+                } else {
+                    **pity += 1;
+                }
+                return Some(BonusOffer {
+                    bonus,
+                    element,
+                    rarity: tier.rarity,
+                });
+            }
+            tiers.retain(|candidate| candidate.rarity != tier.rarity);
+        }
+        None
+    }
+}
+
+impl Default for BonusPool {
+    fn default() -> Self {
+        BonusPool {
+            pity_threshold: 8,
+            tiers: vec![
+                RarityTier {
+                    rarity: Rarity::Common,
+                    weights: BTreeMap::from([(Level::Three, 60)]),
+                    bonuses: vec![
+                        (BonusKind::AttackPlus, 6),
+                        (BonusKind::DefensePlus, 2),
+                        (BonusKind::Heal, 1),
+                    ],
+                },
+                RarityTier {
+                    rarity: Rarity::Uncommon,
+                    weights: BTreeMap::from([(Level::Three, 25)]),
+                    bonuses: vec![
+                        (BonusKind::Enchant, 3),
+                        (BonusKind::DoubleDown, 2),
+                        (BonusKind::MaxHealthPlus, 2),
+                        (BonusKind::SwapAugmentation, 1),
+                    ],
+                },
+                RarityTier {
+                    rarity: Rarity::Rare,
+                    weights: BTreeMap::from([(Level::Three, 15)]),
+                    bonuses: vec![
+                        (BonusKind::Augment(Augmentation::Armored), 1),
+                        (BonusKind::Augment(Augmentation::Combo), 1),
+                        (BonusKind::Augment(Augmentation::Parry), 1),
+                        (BonusKind::RemoveElement, 1),
+                        (BonusKind::Transmute, 1),
+                    ],
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct BonusPoolHandle(pub Handle<BonusPool>);
+
+impl FromWorld for BonusPoolHandle {
+    fn from_world(world: &mut World) -> Self {
+        BonusPoolHandle(world.load_asset("data/default.bonuses.ron"))
+    }
+}
+
+/// Provides the currently loaded [`BonusPool`] to systems.
+#[derive(SystemParam)]
+pub struct CurrentBonusPool<'w> {
+    handle: Res<'w, BonusPoolHandle>,
+    pools: Res<'w, Assets<BonusPool>>,
+}
+
+impl CurrentBonusPool<'_> {
+    /// Returns the loaded bonus pool, or the built-in pool if the data file is not available.
+    pub fn get(&self) -> &BonusPool {
+        static DEFAULT: OnceLock<BonusPool> = OnceLock::new();
+        self.pools.get(&self.handle.0).unwrap_or_else(|| {
+            bevy::log::warn_once!("Bonus pool data is not loaded, using the default pool");
+            DEFAULT.get_or_init(BonusPool::default)
+        })
+    }
+}
+
+/// Counts the bonuses offered since the last rare bonus, for bad-luck protection.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Deref, DerefMut)]
+#[derive(Component)]
+pub struct BonusPity(pub u32);
+
+/// The limited actions a player may take on the bonus screen instead of picking an upgrade.
+/// These are shared across the whole campaign run.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct BonusActions {
+    pub rerolls: u32,
+    pub banishes: u32,
+}
+
+impl BonusActions {
+    /// The HP healed when skipping the bonus screen.
+    pub const SKIP_HEAL: u32 = 3;
+}
+
+impl Default for BonusActions {
+    fn default() -> Self {
+        BonusActions {
+            rerolls: 3,
+            banishes: 2,
+        }
+    }
+}
+
+/// Bonus kinds which the player has removed from the pool for the rest of the run.
+#[derive(Clone, Debug, Default)]
+#[derive(Deref, DerefMut)]
+#[derive(Component)]
+pub struct BanishedBonuses(pub BTreeSet<BonusKind>);
+
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct Upgrades {
+    pub bonuses: Vec<BonusOffer>,
+    pub evolutions: BTreeSet<Element>,
+    pub relic: Option<Relic>,
+}
+
+impl Upgrades {
+    pub fn generate(
+        pool: &BonusPool,
+        offers: RewardOffers,
+        level: Level,
+        player_elements: &PlayerElements,
+        banished: &BanishedBonuses,
+        pity: &mut BonusPity,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        // calculate bonus options for the upgrade option
+        let bonuses = (0..offers.bonuses)
+            .filter_map(|_| pool.roll(level, player_elements, banished, pity, rng))
+            .collect();
+        // calculate new elements for the evolution option
+        let all_elements = BTreeSet::from(Element::ALL);
+        let remaining_elements = all_elements
+            .difference(&player_elements.elements)
+            .cloned()
+            .collect::<Vec<_>>();
+        let evolutions =
+            Element::random_subset(rng, remaining_elements.as_slice(), offers.evolutions);
+        Upgrades {
+            bonuses,
+            evolutions,
+            relic: None,
+        }
+    }
+}
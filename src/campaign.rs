@@ -1,18 +1,27 @@
 use derive_more::derive::{Deref, DerefMut};
-use rand::RngCore;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    RngCore, SeedableRng,
+};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 
 use bevy::prelude::{
     any_with_component, Added, App, Changed, Commands, Component, Entity, Event, IntoSystemConfigs,
-    Name, Plugin, Query, Single, SystemSet, Trigger, Update, With, Without,
+    Name, Plugin, Query, Res, ResMut, Single, SystemSet, Trigger, Update, With, Without,
 };
 use bevy_prng::WyRand;
-use bevy_rand::{global::GlobalEntropy as BRGlobalEntropy, plugin::EntropyPlugin};
+use bevy_rand::{
+    global::GlobalEntropy as BRGlobalEntropy, plugin::EntropyPlugin, prelude::Entropy,
+};
 
 use crate::{
     ui::{AppScreen, BonusUIComponent, GameOverUIComponent, GameUIComponent},
-    Aspect, Augmentation, Element, ElementTable, Game, GameOver, Guess, Health, InGame, Payout,
-    Player, PlayerElements, SpawnGame,
+    Ascension, Aspect, Augmentation, BanishedBonuses, Bonus, BonusActions, BonusPity,
+    CampaignDefinition, CampaignDefinitionHandle, CampaignProgress, CurrentBonusPool,
+    CurrentCampaignDefinition, Element, ElementTable, ForcedAction, Game, GameOver, GameRules,
+    Guess, Health, InGame, MaxHealth, Mutators, Player, PlayerClass, PlayerElements, Profile,
+    Relic, Relics, RonAssetPlugin, RoundHistory, SpawnGame, Upgrades,
 };
 
 type GlobalEntropy<'w> = BRGlobalEntropy<'w, WyRand>;
@@ -22,7 +31,15 @@ pub struct CampaignPlugin;
 impl Plugin for CampaignPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EntropyPlugin::<WyRand>::default());
+        app.add_plugins(RonAssetPlugin::<CampaignDefinition>::new(&["campaign.ron"]));
+        app.init_resource::<CampaignDefinitionHandle>();
         app.add_observer(SpawnCampaign::observer);
+        app.add_observer(RerollUpgrades::observer);
+        app.add_observer(BanishBonus::observer);
+        app.add_observer(SkipBonus::observer);
+        app.add_observer(ChooseBonus::observer);
+        app.add_observer(ChooseEvolution::observer);
+        app.add_observer(StartEndless::observer);
         app.add_systems(
             Update,
             (
@@ -38,39 +55,122 @@ impl Plugin for CampaignPlugin {
 }
 
 impl CampaignPlugin {
-    #[allow(clippy::type_complexity)]
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
     fn change_phases(
         mut commands: Commands,
         mut rng: GlobalEntropy,
         campaign: Option<
             Single<
-                (Entity, &Level, &Phase, &Round, &ElementTable),
+                (
+                    Entity,
+                    &Level,
+                    &Phase,
+                    &Round,
+                    &ElementTable,
+                    &Encounter,
+                    &mut BonusPity,
+                    &BanishedBonuses,
+                    &mut BonusActions,
+                    Option<&Endless>,
+                    &Ascension,
+                    &CampaignProgress,
+                    &Mutators,
+                    Option<&UnlockedAscension>,
+                ),
                 (Changed<Phase>, With<Campaign>),
             >,
         >,
-        player: Single<(Entity, &PlayerElements), With<CampaignPlayer>>,
+        player: Single<
+            (
+                Entity,
+                &PlayerElements,
+                &mut Health,
+                &MaxHealth,
+                &mut Relics,
+            ),
+            With<CampaignPlayer>,
+        >,
         mut app_screen: Single<&mut AppScreen>,
+        bonus_pool: CurrentBonusPool,
+        definition: CurrentCampaignDefinition,
+        mut profile: ResMut<Profile>,
     ) {
         let Some(campaign) = campaign else {
             return;
         };
-        let level = campaign.1;
-        let phase = campaign.2;
-        let (player, elements) = (player.0, player.1);
+        let (
+            campaign_entity,
+            level,
+            phase,
+            round,
+            table,
+            encounter,
+            mut pity,
+            banished,
+            mut bonus_actions,
+            endless,
+            ascension,
+            progress,
+            mutators,
+            unlocked_ascension,
+        ) = campaign.into_inner();
+        let definition = definition.get();
+        let (player, elements, mut health, max_health, mut relics) = player.into_inner();
+
+        relics.on_phase_change(phase, &mut health, max_health);
 
         match phase {
             Phase::InGame => {
-                let enemy_elements =
-                    PlayerElements::from_set(Element::random_set(&mut **rng, level.num_elements()));
+                let encounter = Encounter::new(definition, progress, *round, endless);
+                let mut enemy_elements = encounter.enemy_elements(&mut **rng, *level);
+                let ai_skill = if let Some(endless) = endless {
+                    endless.upgrade_enemy(&mut enemy_elements, *round, &mut **rng);
+                    endless.ai_skill(*round)
+                } else {
+                    AiSkill::default()
+                };
+                ascension.upgrade_enemy(&mut enemy_elements, &mut **rng);
+                mutators.upgrade_enemy(&mut enemy_elements, elements);
+                profile.record_elements(
+                    elements
+                        .elements
+                        .iter()
+                        .chain(enemy_elements.elements.iter())
+                        .cloned(),
+                );
+                let revealed_enemy_elements = if relics.contains(&Relic::Foresight) {
+                    enemy_elements
+                        .augmentations
+                        .keys()
+                        .cloned()
+                        .choose(&mut **rng)
+                        .into_iter()
+                        .collect()
+                } else {
+                    BTreeSet::default()
+                };
                 let enemy = commands
-                    .entity(campaign.0)
-                    .insert((Health::new(5), enemy_elements.clone(), Player))
+                    .entity(campaign_entity)
+                    .insert((enemy_elements.clone(), encounter, ai_skill, Player))
                     .id();
 
                 commands.trigger(SpawnGame {
                     player_one: Some(player),
                     player_two: Some(enemy),
-                    table: Some(campaign.4.clone()),
+                    table: Some(table.clone()),
+                    rules: Some(GameRules {
+                        starting_health: (
+                            None,
+                            Some(
+                                *definition.enemy_health.get(encounter, progress)
+                                    + endless
+                                        .map_or(0, |endless| endless.enemy_bonus_health(*round)),
+                            ),
+                        ),
+                        draw_damage: definition.game_rules.draw_damage + ascension.draw_damage(),
+                        ..definition.game_rules.clone()
+                    }),
+                    mutators: Some(mutators.clone()),
                     ..Default::default()
                 });
 
@@ -78,65 +178,69 @@ impl CampaignPlugin {
                     player,
                     player_elements: elements.clone(),
                     enemy_elements,
-                    table: campaign.4.clone(),
-                    round: *campaign.3,
+                    revealed_enemy_elements,
+                    relics: relics.to_vec(),
+                    table: table.clone(),
+                    round: *round,
+                    encounter,
+                    progress: endless
+                        .is_none()
+                        .then(|| definition.describe_progress(progress)),
+                    seat: None,
+                    spectating: false,
                 });
                 //  (player, elements.clone(), *campaign.4));
             }
             Phase::ProvidingBonus => {
-                // calculate two bonus options for the upgrade option
-                let possible_bonus_elements = elements.elements.iter().cloned().collect::<Vec<_>>();
-                let bonus_element_1 =
-                    Element::random_item(&mut **rng, possible_bonus_elements.as_slice());
-                let bonus_element_2 =
-                    Element::random_item(&mut **rng, possible_bonus_elements.as_slice());
-                let bonus_element_3 =
-                    Element::random_item(&mut **rng, possible_bonus_elements.as_slice());
-                let bonus_choices = [
-                    (
-                        Bonus::new_random(bonus_element_1, &mut **rng),
-                        bonus_element_1,
-                    ),
-                    (
-                        Bonus::new_random(bonus_element_2, &mut **rng),
-                        bonus_element_2,
-                    ),
-                    (
-                        Bonus::new_random(bonus_element_3, &mut **rng),
-                        bonus_element_3,
-                    ),
-                ];
-                // calculate two new elements for the evolution option
-                let all_elements = BTreeSet::from(Element::ALL);
-                let remaining_elements = all_elements
-                    .difference(&elements.elements)
-                    .cloned()
-                    .collect::<Vec<_>>();
-                let evolution_choices =
-                    Element::random_subset(&mut **rng, remaining_elements.as_slice(), 2);
+                let mut upgrades = Upgrades::generate(
+                    bonus_pool.get(),
+                    mutators.reward_offers(definition.reward_offers(progress, ascension)),
+                    *level,
+                    elements,
+                    banished,
+                    &mut pity,
+                    &mut **rng,
+                );
+                // elites and bosses always drop a relic, other battles may lead to an event
+                let relic = if encounter.grants_relic(&mut **rng) {
+                    Relic::random_without(&mut **rng, &relics, &profile.locked_relics())
+                } else {
+                    None
+                };
+                if let Some(relic) = relic {
+                    relics.push(relic);
+                }
+                upgrades.relic = relic;
+                // endless milestone bosses also restore some of the player's bonus actions
+                if endless.is_some() && matches!(encounter, Encounter::Boss) {
+                    bonus_actions.rerolls += 1;
+                    bonus_actions.banishes += 1;
+                }
 
+                commands.entity(campaign_entity).insert(upgrades.clone());
                 **app_screen = AppScreen::ProvidingBonus(BonusUIComponent::new(
-                    campaign.4.clone(),
-                    Upgrades {
-                        bonuses: bonus_choices.to_vec(),
-                        evolutions: evolution_choices,
-                    },
+                    table.clone(),
+                    upgrades,
+                    *bonus_actions,
                 ));
             }
             Phase::GameOver => {
-                **app_screen = AppScreen::GameOver(GameOverUIComponent::loss(
-                    campaign.0,
-                    player,
-                    *campaign.3,
-                    *campaign.1,
-                ));
+                let game_over =
+                    GameOverUIComponent::loss(campaign_entity, player, *round, *level, *ascension);
+                **app_screen = AppScreen::GameOver(if endless.is_some() {
+                    game_over.with_endless(profile.best_endless_round)
+                } else {
+                    game_over
+                });
             }
             Phase::Victory => {
                 **app_screen = AppScreen::GameOver(GameOverUIComponent::victory(
-                    campaign.0,
+                    campaign_entity,
                     player,
-                    *campaign.3,
-                    *campaign.1,
+                    *round,
+                    *level,
+                    *ascension,
+                    unlocked_ascension.map(|unlocked| **unlocked),
                 ));
             }
         }
@@ -145,8 +249,19 @@ impl CampaignPlugin {
     #[allow(clippy::type_complexity)]
     fn detect_game_over(
         mut commands: Commands,
-        mut campaign: Single<(&mut Phase, &mut Round, &Campaign, &Level)>,
+        mut campaign: Single<(
+            &mut Phase,
+            &mut Round,
+            &Campaign,
+            &mut CampaignProgress,
+            Option<&Endless>,
+            &Ascension,
+            &Mutators,
+            Entity,
+        )>,
         game_overs: Option<Single<(Entity, &GameOver), (With<Game>, Added<GameOver>)>>,
+        definition: CurrentCampaignDefinition,
+        mut profile: ResMut<Profile>,
     ) {
         let Campaign { player } = campaign.2;
         let player = *player;
@@ -158,8 +273,20 @@ impl CampaignPlugin {
         match game_over {
             GameOver::Winner(entity) => {
                 if *entity == player {
-                    if matches!(campaign.3, Level::Seven) {
+                    campaign.3.battle += 1;
+                    if definition.get().is_victory(&campaign.3, *campaign.1) && campaign.4.is_none()
+                    {
                         *campaign.0 = Phase::Victory;
+                        let unlocked = campaign
+                            .6
+                            .is_ranked()
+                            .then(|| profile.record_victory(*campaign.5))
+                            .flatten();
+                        if let Some(unlocked) = unlocked {
+                            commands
+                                .entity(campaign.7)
+                                .insert(UnlockedAscension(unlocked));
+                        }
                     } else {
                         *campaign.0 = Phase::ProvidingBonus;
                         **campaign.1 += 1;
@@ -172,25 +299,55 @@ impl CampaignPlugin {
                 *campaign.0 = Phase::GameOver;
             }
         }
+        if matches!(*campaign.0, Phase::GameOver) && campaign.4.is_some() && campaign.6.is_ranked()
+        {
+            profile.record_endless_round(**campaign.1);
+        }
 
         commands.entity(*game_entity).despawn();
         commands.entity(player).remove::<InGame>();
     }
 
+    /// Throws for the campaign's enemy, which is the campaign entity itself, while it is in a game.
+    /// Other players, such as those of hotseat or online games, are never thrown for.
     #[allow(clippy::type_complexity)]
     fn make_enemy_guesses(
         mut commands: Commands,
         mut rng: GlobalEntropy,
-        ai_players: Query<(Entity, &PlayerElements), (Without<CampaignPlayer>, Without<Guess>)>,
+        ai_players: Query<
+            (
+                Entity,
+                &PlayerElements,
+                Option<&AiSkill>,
+                &InGame,
+                Option<&ForcedAction>,
+            ),
+            (With<Campaign>, Without<Guess>),
+        >,
+        games: Query<(&Game, &ElementTable, &RoundHistory)>,
     ) {
-        for (player, elements) in &ai_players {
+        for (player, elements, skill, in_game, forced_action) in &ai_players {
+            // the enemy keeps pointing at its last game until the next one begins
+            let Ok((game, table, history)) = games.get(**in_game) else {
+                continue;
+            };
+            // a staggered enemy knows it must repeat its last throw
+            if let Some(ForcedAction(forced)) = forced_action {
+                commands.entity(player).insert(Guess::new(*forced));
+                continue;
+            }
             let elements = elements.elements.iter().cloned().collect::<Vec<_>>();
-            commands
-                .entity(player)
-                .insert(Guess::new(Element::random_item(
-                    &mut **rng,
-                    elements.as_slice(),
-                )));
+            let opponent_last_guess = game.seat(player).and_then(|seat| {
+                let action = history.last_throws(seat.opponent(), 1).next()?;
+                Some((table, action.guess))
+            });
+            let guess = match (skill, opponent_last_guess) {
+                (Some(skill), Some((table, opponent_guess))) => {
+                    skill.choose_guess(&mut **rng, &elements, table, opponent_guess)
+                }
+                _ => Element::random_item(&mut **rng, elements.as_slice()),
+            };
+            commands.entity(player).insert(Guess::new(guess));
         }
     }
 }
@@ -204,22 +361,283 @@ pub struct CampaignSystems;
 #[derive(Event)]
 pub struct SpawnCampaign {
     pub player: Option<Entity>,
+    pub ascension: Ascension,
+    pub player_class: PlayerClass,
+    pub mutators: Mutators,
 }
 
 impl SpawnCampaign {
-    fn observer(trigger: Trigger<Self>, mut commands: Commands) {
+    fn observer(
+        trigger: Trigger<Self>,
+        mut commands: Commands,
+        mut rng: GlobalEntropy,
+        definition: CurrentCampaignDefinition,
+        profile: Res<Profile>,
+    ) {
         let player = trigger
             .event()
             .player
             .unwrap_or_else(|| commands.spawn_empty().id());
-        let campaign = commands.spawn(Campaign { player }).id();
+        let SpawnCampaign {
+            ascension,
+            player_class,
+            ref mutators,
+            ..
+        } = *trigger.event();
+        // every run is driven by its own seed so that it can be recorded and replayed
+        let seed = rng.next_u64();
+        **rng = Entropy::new(WyRand::seed_from_u64(seed));
+        // bonuses which are not unlocked yet are kept out of the run just like banished ones
+        let campaign = commands
+            .spawn((
+                Campaign { player },
+                RunSeed(seed),
+                definition.get().level(0),
+                ascension,
+                player_class,
+                mutators.clone(),
+                BanishedBonuses(profile.locked_bonuses()),
+            ))
+            .id();
+        let health = ascension.player_health(player_class.starting_health());
         commands
             .entity(player)
-            .insert((Health::PLAYER_MAX, CampaignPlayer, InCampaign(campaign)))
+            .insert((
+                MaxHealth::from(&health),
+                health,
+                player_class.player_elements(),
+                Relics(vec![player_class.passive()]),
+                CampaignPlayer,
+                InCampaign(campaign),
+            ))
             .insert_if_new(Player);
     }
 }
 
+/// Regenerates the upgrades offered on the bonus screen, using up one reroll.
+#[derive(Debug)]
+#[derive(Event)]
+pub struct RerollUpgrades;
+
+impl RerollUpgrades {
+    #[allow(clippy::type_complexity)]
+    fn observer(
+        _trigger: Trigger<Self>,
+        mut rng: GlobalEntropy,
+        campaign: Single<
+            (
+                &Level,
+                &ElementTable,
+                &mut BonusPity,
+                &BanishedBonuses,
+                &mut BonusActions,
+                &mut Upgrades,
+                &Ascension,
+                &CampaignProgress,
+                &Mutators,
+            ),
+            With<Campaign>,
+        >,
+        player: Single<&PlayerElements, With<CampaignPlayer>>,
+        mut app_screen: Single<&mut AppScreen>,
+        bonus_pool: CurrentBonusPool,
+        definition: CurrentCampaignDefinition,
+    ) {
+        let (
+            level,
+            table,
+            mut pity,
+            banished,
+            mut bonus_actions,
+            mut upgrades,
+            ascension,
+            progress,
+            mutators,
+        ) = campaign.into_inner();
+        let Some(rerolls) = bonus_actions.rerolls.checked_sub(1) else {
+            return;
+        };
+        bonus_actions.rerolls = rerolls;
+        *upgrades = Upgrades {
+            relic: upgrades.relic,
+            ..Upgrades::generate(
+                bonus_pool.get(),
+                mutators.reward_offers(definition.get().reward_offers(progress, ascension)),
+                *level,
+                &player,
+                banished,
+                &mut pity,
+                &mut **rng,
+            )
+        };
+        **app_screen = AppScreen::ProvidingBonus(BonusUIComponent::new(
+            table.clone(),
+            upgrades.clone(),
+            *bonus_actions,
+        ));
+    }
+}
+
+/// Permanently removes the kind of an offered bonus from the run's pool,
+/// replacing the offer with a new one.
+#[derive(Debug)]
+#[derive(Event)]
+pub struct BanishBonus {
+    pub index: usize,
+}
+
+impl BanishBonus {
+    #[allow(clippy::type_complexity)]
+    fn observer(
+        trigger: Trigger<Self>,
+        mut rng: GlobalEntropy,
+        campaign: Single<
+            (
+                &Level,
+                &ElementTable,
+                &mut BonusPity,
+                &mut BanishedBonuses,
+                &mut BonusActions,
+                &mut Upgrades,
+            ),
+            With<Campaign>,
+        >,
+        player: Single<&PlayerElements, With<CampaignPlayer>>,
+        mut app_screen: Single<&mut AppScreen>,
+        bonus_pool: CurrentBonusPool,
+    ) {
+        let (level, table, mut pity, mut banished, mut bonus_actions, mut upgrades) =
+            campaign.into_inner();
+        let index = trigger.event().index;
+        let Some(offer) = upgrades.bonuses.get(index) else {
+            return;
+        };
+        let Some(banishes) = bonus_actions.banishes.checked_sub(1) else {
+            return;
+        };
+        bonus_actions.banishes = banishes;
+        banished.insert(offer.bonus.kind());
+        let replacement = bonus_pool
+            .get()
+            .roll(*level, &player, &banished, &mut pity, &mut **rng);
+        if let Some(replacement) = replacement {
+            upgrades.bonuses[index] = replacement;
+        } else {
+            upgrades.bonuses.remove(index);
+        }
+        **app_screen = AppScreen::ProvidingBonus(BonusUIComponent::new(
+            table.clone(),
+            upgrades.clone(),
+            *bonus_actions,
+        ));
+    }
+}
+
+/// Applies one of the offered bonuses to the campaign player and continues to the next battle.
+#[derive(Debug)]
+#[derive(Event)]
+pub struct ChooseBonus {
+    pub bonus: Bonus,
+    pub element: Element,
+}
+
+impl ChooseBonus {
+    #[allow(clippy::type_complexity)]
+    fn observer(
+        trigger: Trigger<Self>,
+        mut commands: Commands,
+        campaign: Single<
+            (
+                &mut ElementTable,
+                &mut Phase,
+                &mut Level,
+                &mut CampaignProgress,
+            ),
+            With<Campaign>,
+        >,
+        player: Single<(&mut PlayerElements, &mut Health, &mut MaxHealth), With<CampaignPlayer>>,
+        definition: CurrentCampaignDefinition,
+        mut profile: ResMut<Profile>,
+    ) {
+        let ChooseBonus { bonus, element } = trigger.event();
+        let (mut table, mut phase, mut level, mut progress) = campaign.into_inner();
+        let (mut elements, mut health, mut max_health) = player.into_inner();
+        if let Err(error) = bonus.update_game(
+            &mut table,
+            &mut elements,
+            &mut health,
+            &mut max_health,
+            *element,
+        ) {
+            bevy::log::warn!(
+                "Failed to apply bonus {}: {error}",
+                bonus.get_readable_name()
+            );
+            return;
+        }
+        profile.record_bonus(bonus.kind());
+        commands.trigger(BonusApplied {
+            bonus: bonus.clone(),
+            element: *element,
+        });
+        if let Some(next_level) = definition.get().advance_without_evolution(&mut progress) {
+            *level = next_level;
+        }
+        *phase = Phase::InGame;
+    }
+}
+
+/// A chosen bonus was successfully applied to the campaign player.
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct BonusApplied {
+    pub bonus: Bonus,
+    pub element: Element,
+}
+
+/// Adds a new element to the campaign player and advances to the next level.
+#[derive(Debug)]
+#[derive(Event)]
+pub struct ChooseEvolution {
+    pub element: Element,
+}
+
+impl ChooseEvolution {
+    fn observer(
+        trigger: Trigger<Self>,
+        campaign: Single<(&mut Phase, &mut Level, &mut CampaignProgress), With<Campaign>>,
+        mut elements: Single<&mut PlayerElements, With<CampaignPlayer>>,
+        definition: CurrentCampaignDefinition,
+    ) {
+        let (mut phase, mut level, mut progress) = campaign.into_inner();
+        elements.insert(trigger.event().element);
+        *level = definition.get().advance_level(&mut progress);
+        *phase = Phase::InGame;
+    }
+}
+
+/// Passes on the offered upgrades in exchange for a small heal.
+#[derive(Debug)]
+#[derive(Event)]
+pub struct SkipBonus;
+
+impl SkipBonus {
+    fn observer(
+        _trigger: Trigger<Self>,
+        campaign: Single<(&mut Phase, &mut Level, &mut CampaignProgress), With<Campaign>>,
+        player: Single<(&mut Health, &MaxHealth), With<CampaignPlayer>>,
+        definition: CurrentCampaignDefinition,
+    ) {
+        let (mut phase, mut level, mut progress) = campaign.into_inner();
+        let (mut health, max_health) = player.into_inner();
+        health.heal(BonusActions::SKIP_HEAL, max_health);
+        if let Some(next_level) = definition.get().advance_without_evolution(&mut progress) {
+            *level = next_level;
+        }
+        *phase = Phase::InGame;
+    }
+}
+
 #[derive(Component)]
 struct CampaignPlayer;
 
@@ -229,11 +647,23 @@ struct InCampaign(pub Entity);
 
 #[derive(Debug)]
 #[derive(Component)]
-#[require(Level, Round, Phase, ElementTable, PlayerElements, Name(|| Name::new("Campaign")))]
+#[require(Level, Round, Phase, Encounter, CampaignProgress, BonusPity, BonusActions, BanishedBonuses, ElementTable, PlayerElements, Mutators, Name(|| Name::new("Campaign")))]
 pub struct Campaign {
     pub player: Entity,
 }
 
+/// The ascension tier first unlocked by winning the campaign.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deref)]
+#[derive(Component)]
+pub struct UnlockedAscension(pub Ascension);
+
+/// The seed the global random number generator was reset to when the campaign began.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deref)]
+#[derive(Component)]
+pub struct RunSeed(pub u64);
+
 #[derive(Clone, Copy, Debug)]
 #[derive(Deref, DerefMut)]
 #[derive(Component)]
@@ -245,7 +675,8 @@ impl Default for Round {
     }
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deserialize, Serialize)]
 #[derive(Component)]
 pub enum Level {
     #[default]
@@ -279,115 +710,179 @@ impl Level {
     }
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+/// The kind of enemy faced in a campaign battle.
+/// Elites and bosses are tougher than regular battles, but always reward a relic.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize)]
 #[derive(Component)]
-pub enum Phase {
+pub enum Encounter {
     #[default]
-    InGame,
-    ProvidingBonus,
-    GameOver,
-    Victory,
+    Battle,
+    Elite,
+    Boss,
 }
 
-#[derive(Clone, Debug)]
-pub enum Bonus {
-    AttackPlus { enemy_element: Element },
-    DefensePlus { enemy_element: Element },
-    DoubleDown,
-    Augment(Augmentation),
-    Enchant(Aspect),
-}
-
-impl Bonus {
-    pub fn new_random(bonus_element: Element, rng: &mut impl RngCore) -> Self {
-        let random_percent = rand::seq::index::sample(rng, 100, 1).index(0);
-        match random_percent {
-            0..45 => Bonus::AttackPlus {
-                enemy_element: Element::random(rng),
-            },
-            45..60 => Bonus::DefensePlus {
-                enemy_element: Element::random(rng),
-            },
-            60..65 => Bonus::Augment(Augmentation::Armored),
-            65..70 => Bonus::Augment(Augmentation::Combo),
-            70..75 => Bonus::Augment(Augmentation::Parry),
-            75..90 => Bonus::Enchant(Aspect::new(Element::random_without(rng, bonus_element))),
-            _ => Bonus::DoubleDown,
+impl Encounter {
+    const EVENT_PERCENT: usize = 10;
+
+    pub fn new(
+        definition: &CampaignDefinition,
+        progress: &CampaignProgress,
+        round: Round,
+        endless: Option<&Endless>,
+    ) -> Self {
+        if let Some(endless) = endless {
+            if endless.is_milestone(round) {
+                Encounter::Boss
+            } else if round.is_multiple_of(definition.elite_frequency.max(1)) {
+                Encounter::Elite
+            } else {
+                Encounter::Battle
+            }
+        } else {
+            definition.encounter(progress, round)
         }
     }
 
-    pub fn get_readable_name(&self) -> String {
-        match self {
-            Bonus::AttackPlus { enemy_element } => format!("Attack+ vs. {enemy_element}"),
-            Bonus::DefensePlus { enemy_element } => format!("Defense+ vs. {enemy_element}"),
-            Bonus::DoubleDown => "Double Down".to_string(),
-            Bonus::Augment(augmentation) => format!("Augmentation: {augmentation}"),
-            Bonus::Enchant(aspect) => format!("Enchantment: Aspect of {}", **aspect),
+    pub fn enemy_elements(&self, rng: &mut impl RngCore, level: Level) -> PlayerElements {
+        let mut elements = PlayerElements::from_set(Element::random_set(rng, level.num_elements()));
+        let num_augmentations = match self {
+            Encounter::Battle => 0,
+            Encounter::Elite => 1,
+            Encounter::Boss => 2,
+        };
+        let augmented_elements = elements
+            .elements
+            .iter()
+            .cloned()
+            .choose_multiple(rng, num_augmentations);
+        for element in augmented_elements {
+            let augmentation = *Augmentation::ALL.choose(rng).unwrap();
+            if let Err(error) = elements.augment(element, augmentation) {
+                bevy::log::warn!("Failed to augment enemy element: {error}");
+            }
         }
+        elements
     }
 
-    pub fn get_description(&self, element: Element) -> String {
+    pub fn grants_relic(&self, rng: &mut impl RngCore) -> bool {
         match self {
-            Bonus::AttackPlus { enemy_element } => {
-                format!("{element} deals 1 additional damage against {enemy_element}")
+            Encounter::Battle => {
+                rand::seq::index::sample(rng, 100, 1).index(0) < Self::EVENT_PERCENT
             }
-            Bonus::DefensePlus { enemy_element } => format!(
-                "{element} receives 1 less damage (or heals 1 more HP) against {enemy_element}"
-            ),
-            Bonus::DoubleDown => format!("Doubles all payouts for {element}."),
-            Bonus::Augment(augmentation) => augmentation.get_description(element),
-            Bonus::Enchant(aspect) => aspect.get_description(element),
+            Encounter::Elite | Encounter::Boss => true,
         }
     }
+}
+
+/// Marks a campaign which continues past victory.
+/// Enemies keep scaling with each round, and every few rounds a milestone boss grants a reward.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct Endless {
+    pub start_round: Round,
+}
+
+impl Endless {
+    pub const MILESTONE_FREQUENCY: u16 = 5;
+
+    /// The number of rounds played since endless mode began.
+    pub fn depth(&self, round: Round) -> u16 {
+        round.saturating_sub(*self.start_round)
+    }
+
+    pub fn is_milestone(&self, round: Round) -> bool {
+        let depth = self.depth(round);
+        depth > 0 && depth.is_multiple_of(Self::MILESTONE_FREQUENCY)
+    }
+
+    pub fn enemy_bonus_health(&self, round: Round) -> u32 {
+        u32::from(self.depth(round) / 2)
+    }
 
-    pub fn update_game(
+    pub fn ai_skill(&self, round: Round) -> AiSkill {
+        AiSkill((u32::from(self.depth(round)) * 5).min(AiSkill::MAX))
+    }
+
+    /// Grants the enemy additional augmentations and aspects based on how deep the run is.
+    pub fn upgrade_enemy(
         &self,
-        table: &mut ElementTable,
-        player_elements: &mut PlayerElements,
-        element_to_upgrade: Element,
+        elements: &mut PlayerElements,
+        round: Round,
+        rng: &mut impl RngCore,
     ) {
-        if !player_elements.elements.contains(&element_to_upgrade) {
-            return;
-        }
-        match self {
-            Bonus::AttackPlus { enemy_element } => {
-                table.update(
-                    element_to_upgrade,
-                    *enemy_element,
-                    Payout {
-                        damage_to_enemy: 1,
-                        damage_to_me: 0,
-                    },
-                );
-            }
-            Bonus::DefensePlus { enemy_element } => {
-                table.update(
-                    element_to_upgrade,
-                    *enemy_element,
-                    Payout {
-                        damage_to_enemy: 0,
-                        damage_to_me: -1,
-                    },
-                );
-            }
-            Bonus::DoubleDown => {
-                for enemy_element in Element::ALL {
-                    table.double(element_to_upgrade, enemy_element);
-                }
+        let depth = usize::from(self.depth(round));
+        let candidates = elements.elements.iter().cloned().collect::<Vec<_>>();
+        for element in candidates.choose_multiple(rng, depth / 3) {
+            let augmentation = *Augmentation::ALL.choose(rng).unwrap();
+            if let Err(error) = elements.augment(*element, augmentation) {
+                bevy::log::warn!("Failed to augment enemy element: {error}");
             }
-            Bonus::Augment(augmentation) => {
-                player_elements.augment(element_to_upgrade, *augmentation);
+        }
+        for element in candidates.choose_multiple(rng, depth / 4) {
+            let aspect = Aspect::new(Element::random_without(rng, *element));
+            if let Err(error) = elements.enchant(*element, aspect) {
+                bevy::log::warn!("Failed to enchant enemy element: {error}");
             }
-            Bonus::Enchant(aspect) => {
-                player_elements.enchant(element_to_upgrade, *aspect);
+        }
+    }
+}
+
+/// Continues a won campaign in endless mode.
+#[derive(Debug)]
+#[derive(Event)]
+pub struct StartEndless;
+
+impl StartEndless {
+    fn observer(
+        _trigger: Trigger<Self>,
+        mut commands: Commands,
+        campaign: Single<(Entity, &mut Phase, &Round), With<Campaign>>,
+    ) {
+        let (campaign, mut phase, round) = campaign.into_inner();
+        commands.entity(campaign).insert(Endless {
+            start_round: *round,
+        });
+        *phase = Phase::ProvidingBonus;
+    }
+}
+
+/// The percent chance that an enemy counters the opponent's previous throw rather than guessing randomly.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Deref, DerefMut)]
+#[derive(Component)]
+pub struct AiSkill(pub u32);
+
+impl AiSkill {
+    pub const MAX: u32 = 75;
+
+    pub fn choose_guess(
+        &self,
+        rng: &mut impl RngCore,
+        elements: &[Element],
+        table: &ElementTable,
+        opponent_last_guess: Element,
+    ) -> Element {
+        let is_countering = rand::seq::index::sample(rng, 100, 1).index(0) < self.0 as usize;
+        if is_countering {
+            let best_counter = elements.iter().max_by_key(|element| {
+                let payout = table.evaluate(**element, opponent_last_guess);
+                payout.damage_to_enemy - payout.damage_to_me
+            });
+            if let Some(element) = best_counter {
+                return *element;
             }
         }
+        Element::random_item(rng, elements)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Default, Debug)]
 #[derive(Component)]
-pub struct Upgrades {
-    pub bonuses: Vec<(Bonus, Element)>,
-    pub evolutions: BTreeSet<Element>,
+pub enum Phase {
+    #[default]
+    InGame,
+    ProvidingBonus,
+    GameOver,
+    Victory,
 }
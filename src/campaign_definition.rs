@@ -0,0 +1,300 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use bevy::{
+    asset::{Asset, Assets, Handle},
+    ecs::system::SystemParam,
+    prelude::{Component, DirectAssetAccessExt, FromWorld, Res, Resource, TypePath, World},
+};
+
+use crate::{Ascension, Encounter, GameRules, Health, Level, Round, Tiebreak};
+
+/// The shape of a campaign run, loaded from `assets/data/*.campaign.ron`.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+#[derive(Asset, TypePath)]
+pub struct CampaignDefinition {
+    /// Acts are played in order, and each act is a sequence of levels.
+    pub acts: Vec<Act>,
+    /// The number of battles which make up each level.
+    pub battles_per_level: u16,
+    /// Every this many battles, the enemy is an elite.
+    pub elite_frequency: u16,
+    pub enemy_health: EnemyHealthCurve,
+    pub rewards: RewardOffers,
+    pub evolutions: EvolutionSchedule,
+    pub victory: VictoryCondition,
+    /// The rules every battle is played under.
+    /// The enemy's starting health always comes from `enemy_health`.
+    pub game_rules: GameRules,
+}
+
+impl CampaignDefinition {
+    /// Every level of the campaign, across all acts.
+    pub fn levels(&self) -> impl Iterator<Item = Level> + '_ {
+        self.acts.iter().flat_map(|act| act.levels.iter().cloned())
+    }
+
+    pub fn num_levels(&self) -> usize {
+        self.levels().count()
+    }
+
+    /// The level at `index`, or the final level if the campaign is shorter than that.
+    pub fn level(&self, index: usize) -> Level {
+        self.levels()
+            .nth(index)
+            .or_else(|| self.levels().last())
+            .unwrap_or_default()
+    }
+
+    /// The act containing the level at `index`.
+    pub fn act(&self, index: usize) -> Option<&Act> {
+        let mut first_level = 0;
+        self.acts.iter().find(|act| {
+            first_level += act.levels.len();
+            index < first_level
+        })
+    }
+
+    pub fn is_final_level(&self, progress: &CampaignProgress) -> bool {
+        progress.level_index + 1 >= self.num_levels()
+    }
+
+    pub fn is_level_complete(&self, progress: &CampaignProgress) -> bool {
+        progress.battle >= self.battles_per_level
+    }
+
+    /// Whether evolutions may be offered after the latest battle.
+    pub fn offers_evolution(&self, progress: &CampaignProgress) -> bool {
+        if self.is_final_level(progress) {
+            return false;
+        }
+        match self.evolutions {
+            EvolutionSchedule::AnyBattle => true,
+            EvolutionSchedule::LevelEnd | EvolutionSchedule::Forced => {
+                self.is_level_complete(progress)
+            }
+        }
+    }
+
+    /// Whether the player must choose an evolution, with no bonuses on offer.
+    pub fn is_evolution_forced(&self, progress: &CampaignProgress) -> bool {
+        matches!(self.evolutions, EvolutionSchedule::Forced) && self.offers_evolution(progress)
+    }
+
+    /// Moves on to the next level, returning it.
+    pub fn advance_level(&self, progress: &mut CampaignProgress) -> Level {
+        progress.level_index = (progress.level_index + 1).min(self.num_levels().saturating_sub(1));
+        progress.battle = 0;
+        self.level(progress.level_index)
+    }
+
+    /// Called when the player takes a bonus or skips instead of evolving.
+    /// Once a scheduled level is complete it ends regardless, returning the next level.
+    pub fn advance_without_evolution(&self, progress: &mut CampaignProgress) -> Option<Level> {
+        let is_scheduled = !matches!(self.evolutions, EvolutionSchedule::AnyBattle);
+        if is_scheduled && self.offers_evolution(progress) {
+            Some(self.advance_level(progress))
+        } else {
+            None
+        }
+    }
+
+    pub fn describe_progress(&self, progress: &CampaignProgress) -> LevelProgress {
+        LevelProgress {
+            act: self
+                .act(progress.level_index)
+                .map(|act| act.name.clone())
+                .unwrap_or_default(),
+            level: progress.level_index + 1,
+            num_levels: self.num_levels(),
+            battle: progress.battle + 1,
+            num_battles: self.battles_per_level,
+        }
+    }
+
+    /// Checks for victory after the player wins a battle.
+    pub fn is_victory(&self, progress: &CampaignProgress, round: Round) -> bool {
+        match self.victory {
+            VictoryCondition::CompleteAllLevels => {
+                self.is_final_level(progress) && self.is_level_complete(progress)
+            }
+            VictoryCondition::WinBattles(battles) => *round >= battles,
+        }
+    }
+
+    pub fn reward_offers(
+        &self,
+        progress: &CampaignProgress,
+        ascension: &Ascension,
+    ) -> RewardOffers {
+        RewardOffers {
+            bonuses: if self.is_evolution_forced(progress) {
+                0
+            } else {
+                ascension.num_bonuses(self.rewards.bonuses)
+            },
+            evolutions: if self.offers_evolution(progress) {
+                self.rewards.evolutions
+            } else {
+                0
+            },
+        }
+    }
+
+    pub fn encounter(&self, progress: &CampaignProgress, round: Round) -> Encounter {
+        let is_final_battle = progress.battle + 1 >= self.battles_per_level;
+        if self.is_final_level(progress) && is_final_battle {
+            Encounter::Boss
+        } else if round.is_multiple_of(self.elite_frequency.max(1)) {
+            Encounter::Elite
+        } else {
+            Encounter::Battle
+        }
+    }
+}
+
+impl Default for CampaignDefinition {
+    fn default() -> Self {
+        use Level::*;
+        CampaignDefinition {
+            acts: vec![Act {
+                name: "Paradise".to_string(),
+                levels: vec![Three, Four, Five, Six, Seven],
+            }],
+            battles_per_level: 3,
+            elite_frequency: 4,
+            enemy_health: EnemyHealthCurve {
+                battle: 5,
+                elite: 8,
+                boss: 12,
+                per_level: 0,
+            },
+            rewards: RewardOffers {
+                bonuses: 3,
+                evolutions: 2,
+            },
+            evolutions: EvolutionSchedule::LevelEnd,
+            victory: VictoryCondition::CompleteAllLevels,
+            game_rules: GameRules {
+                tiebreak: Tiebreak::SuddenDeath,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct Act {
+    pub name: String,
+    /// The number of elements enemies use in each level of the act.
+    pub levels: Vec<Level>,
+}
+
+/// Enemy health by encounter, growing with each level.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct EnemyHealthCurve {
+    pub battle: u32,
+    pub elite: u32,
+    pub boss: u32,
+    pub per_level: u32,
+}
+
+impl EnemyHealthCurve {
+    pub fn get(&self, encounter: Encounter, progress: &CampaignProgress) -> Health {
+        let base = match encounter {
+            Encounter::Battle => self.battle,
+            Encounter::Elite => self.elite,
+            Encounter::Boss => self.boss,
+        };
+        Health::new(base + self.per_level * progress.level_index as u32)
+    }
+}
+
+/// The number of options offered after each victory.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct RewardOffers {
+    pub bonuses: usize,
+    pub evolutions: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum EvolutionSchedule {
+    /// Evolutions are offered after every battle.
+    AnyBattle,
+    /// Evolutions are only offered once every battle of the level has been won.
+    /// Taking a bonus instead still ends the level.
+    LevelEnd,
+    /// Like `LevelEnd`, but no bonuses are offered alongside the evolutions.
+    Forced,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum VictoryCondition {
+    /// Win every battle of the final level.
+    CompleteAllLevels,
+    /// Win this many battles in total.
+    WinBattles(u16),
+}
+
+/// How far a campaign has progressed through its [`CampaignDefinition`].
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component)]
+pub struct CampaignProgress {
+    pub level_index: usize,
+    /// The number of battles won in the current level.
+    pub battle: u16,
+}
+
+/// A readable summary of where the player is within the campaign.
+#[derive(Clone, Debug)]
+pub struct LevelProgress {
+    pub act: String,
+    pub level: usize,
+    pub num_levels: usize,
+    pub battle: u16,
+    pub num_battles: u16,
+}
+
+impl std::fmt::Display for LevelProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} - Level {}/{} - Battle {}/{}",
+            self.act, self.level, self.num_levels, self.battle, self.num_battles
+        )
+    }
+}
+
+#[derive(Resource)]
+pub struct CampaignDefinitionHandle(pub Handle<CampaignDefinition>);
+
+impl FromWorld for CampaignDefinitionHandle {
+    fn from_world(world: &mut World) -> Self {
+        CampaignDefinitionHandle(world.load_asset("data/default.campaign.ron"))
+    }
+}
+
+/// Provides the currently loaded [`CampaignDefinition`] to systems.
+#[derive(SystemParam)]
+pub struct CurrentCampaignDefinition<'w> {
+    handle: Res<'w, CampaignDefinitionHandle>,
+    definitions: Res<'w, Assets<CampaignDefinition>>,
+}
+
+impl CurrentCampaignDefinition<'_> {
+    /// Returns the loaded campaign, or the built-in campaign if the data file is not available.
+    pub fn get(&self) -> &CampaignDefinition {
+        static DEFAULT: OnceLock<CampaignDefinition> = OnceLock::new();
+        self.definitions.get(&self.handle.0).unwrap_or_else(|| {
+            bevy::log::warn_once!("Campaign data is not loaded, using the default campaign");
+            DEFAULT.get_or_init(CampaignDefinition::default)
+        })
+    }
+}
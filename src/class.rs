@@ -0,0 +1,76 @@
+use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::Component;
+
+use crate::{Aspect, Augmentation, Element, Health, PlayerElements, Relic};
+
+/// The loadout a campaign run begins with.
+/// Each class's passive is a relic which the player owns from the first battle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Display)]
+#[derive(Deserialize, Serialize)]
+#[derive(Component)]
+pub enum PlayerClass {
+    #[default]
+    Brawler,
+    Elementalist,
+    Warden,
+}
+
+impl PlayerClass {
+    pub const ALL: [Self; 3] = [Self::Brawler, Self::Elementalist, Self::Warden];
+
+    pub fn player_elements(&self) -> PlayerElements {
+        match self {
+            PlayerClass::Brawler => PlayerElements::rock_paper_scissors(),
+            PlayerClass::Elementalist => {
+                let mut elements =
+                    PlayerElements::from_set([Element::Water, Element::Air, Element::Fire]);
+                if let Err(error) = elements.enchant(Element::Fire, Aspect::new(Element::Air)) {
+                    bevy::log::warn!("Failed to set up the {self} class: {error}");
+                }
+                elements
+            }
+            PlayerClass::Warden => {
+                let mut elements =
+                    PlayerElements::from_set([Element::Rock, Element::Earth, Element::Water]);
+                if let Err(error) = elements.augment(Element::Rock, Augmentation::Armored) {
+                    bevy::log::warn!("Failed to set up the {self} class: {error}");
+                }
+                elements
+            }
+        }
+    }
+
+    pub fn starting_health(&self) -> Health {
+        match self {
+            PlayerClass::Brawler => Health::PLAYER_MAX,
+            PlayerClass::Elementalist => Health::new(20),
+            PlayerClass::Warden => Health::new(30),
+        }
+    }
+
+    pub fn passive(&self) -> Relic {
+        match self {
+            PlayerClass::Brawler => Relic::SecondWind,
+            PlayerClass::Elementalist => Relic::OpeningGambit,
+            PlayerClass::Warden => Relic::SteadyHands,
+        }
+    }
+
+    pub fn get_description(&self) -> String {
+        let elements = self
+            .player_elements()
+            .elements
+            .iter()
+            .map(Element::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{elements} | {} HP | Passive: {}",
+            *self.starting_health(),
+            self.passive(),
+        )
+    }
+}
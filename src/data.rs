@@ -0,0 +1,76 @@
+use std::marker::PhantomData;
+
+use derive_more::derive::{Display, Error, From};
+use serde::de::DeserializeOwned;
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetApp, AssetLoader, LoadContext},
+    prelude::{App, Plugin},
+};
+
+/// Registers an [`Asset`] type which is deserialized from a RON data file.
+/// This lets game design values live in `assets/data` rather than in code.
+pub struct RonAssetPlugin<A> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<A>,
+}
+
+impl<A> RonAssetPlugin<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A> Plugin for RonAssetPlugin<A>
+where
+    A: Asset + DeserializeOwned,
+{
+    fn build(&self, app: &mut App) {
+        app.init_asset::<A>()
+            .register_asset_loader(RonAssetLoader::<A> {
+                extensions: self.extensions,
+                _marker: PhantomData,
+            });
+    }
+}
+
+struct RonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<A>,
+}
+
+#[derive(Debug)]
+#[derive(Display, Error, From)]
+pub enum RonAssetLoaderError {
+    #[display("Could not read data file: {_0}")]
+    Io(std::io::Error),
+    #[display("Could not parse data file: {_0}")]
+    Ron(ron::error::SpannedError),
+}
+
+impl<A> AssetLoader for RonAssetLoader<A>
+where
+    A: Asset + DeserializeOwned,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<A>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
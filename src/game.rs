@@ -1,11 +1,21 @@
+use std::cmp::Ordering;
+
 use derive_more::derive::Deref;
+use serde::{Deserialize, Serialize};
 
 use bevy::prelude::{
-    App, Commands, Component, Entity, Event, IntoSystemConfigs, Name, Plugin, Query, SystemSet,
-    Trigger, Update, With, Without,
+    App, Changed, Commands, Component, Entity, Event, IntoSystemConfigs, Name, Plugin, Query, Res,
+    SystemSet, Time, Timer, TimerMode, Trigger, Update, With, Without,
+};
+use bevy_prng::WyRand;
+use bevy_rand::global::GlobalEntropy as BRGlobalEntropy;
+
+use crate::{
+    Action, Combo, Element, ElementTable, Guess, Health, Mutators, Outcome, Player, PlayerElements,
+    PlayerOutcome, Relics, RemoteGame, RoundContext, RoundHistory, RoundRecord, Seat, Stagger,
 };
 
-use crate::{Action, Combo, ElementTable, Guess, Health, Outcome, Player, PlayerElements, Stagger};
+type GlobalEntropy<'w> = BRGlobalEntropy<'w, WyRand>;
 
 pub struct GamePlugin;
 
@@ -14,7 +24,12 @@ impl Plugin for GamePlugin {
         app.add_observer(SpawnGame::observer);
         app.add_systems(
             Update,
-            (Self::throw_hands, Self::detect_game_over)
+            (
+                Self::auto_throw_on_timeout,
+                Self::reject_conflicting_guesses,
+                Self::throw_hands,
+                Self::detect_game_over,
+            )
                 .chain()
                 .in_set(GameSystems),
         );
@@ -22,10 +37,71 @@ impl Plugin for GamePlugin {
 }
 
 impl GamePlugin {
+    /// Throws for every player who is still deciding once the round's countdown runs out.
+    #[allow(clippy::type_complexity)]
+    fn auto_throw_on_timeout(
+        mut commands: Commands,
+        mut rng: GlobalEntropy,
+        time: Res<Time>,
+        mut games: Query<(&Game, &mut ThrowTimer, &RoundHistory), Without<GameOver>>,
+        players: Query<(&PlayerElements, Option<&ForcedAction>), (With<Player>, Without<Guess>)>,
+    ) {
+        for (game, mut throw_timer, history) in &mut games {
+            if !throw_timer.timer.tick(time.delta()).just_finished() {
+                continue;
+            }
+            for (player, seat) in [
+                (game.player_one, Seat::PlayerOne),
+                (game.player_two, Seat::PlayerTwo),
+            ] {
+                let Ok((elements, forced_action)) = players.get(player) else {
+                    continue;
+                };
+                let elements = elements.elements.iter().cloned().collect::<Vec<_>>();
+                let last_throw = history
+                    .last_throws(seat, 1)
+                    .next()
+                    .map(|action| action.guess)
+                    .filter(|guess| elements.contains(guess));
+                let guess = match (throw_timer.rules.on_timeout, last_throw, forced_action) {
+                    (_, _, Some(ForcedAction(forced))) => *forced,
+                    (AutoThrow::RepeatLast, Some(guess), _) => guess,
+                    _ => Element::random_item(&mut **rng, &elements),
+                };
+                commands.entity(player).insert(Guess::new(guess));
+                if let AutoThrow::Penalty(damage) = throw_timer.rules.on_timeout {
+                    commands.entity(player).insert(TimedOut { penalty: damage });
+                }
+            }
+        }
+    }
+
+    /// Discards guesses which ignore the player's forced action.
+    fn reject_conflicting_guesses(
+        mut commands: Commands,
+        players: Query<(Entity, &Guess, &ForcedAction), Changed<Guess>>,
+    ) {
+        for (player, guess, ForcedAction(forced)) in &players {
+            if **guess != *forced {
+                bevy::log::warn!("Rejected {}: the player must repeat {forced}", **guess);
+                commands.entity(player).remove::<Guess>();
+            }
+        }
+    }
+
     #[allow(clippy::type_complexity)]
     fn throw_hands(
         mut commands: Commands,
-        games: Query<(Entity, &Game, &ElementTable)>,
+        mut rng: GlobalEntropy,
+        mut games: Query<(
+            Entity,
+            &Game,
+            &mut RoundHistory,
+            &ElementTable,
+            &GameRules,
+            Option<&Mutators>,
+            Option<&mut ThrowTimer>,
+        )>,
         mut players: Query<
             (
                 &mut Health,
@@ -33,36 +109,87 @@ impl GamePlugin {
                 &Guess,
                 Option<&Combo>,
                 Option<&Stagger>,
+                Option<&Relics>,
+                Option<&TimedOut>,
             ),
             With<Player>,
         >,
     ) {
-        for (game_entity, game, table) in &games {
+        for (game_entity, game, mut history, table, rules, mutators, throw_timer) in &mut games {
             let Ok(
-                [(mut p1_hp, p1_elements, p1_guess, p1_combo, p1_stagger), (mut p2_hp, p2_elements, p2_guess, p2_combo, p2_stagger)],
+                [(mut p1_hp, p1_elements, p1_guess, p1_combo, p1_stagger, p1_relics, p1_timed_out), (mut p2_hp, p2_elements, p2_guess, p2_combo, p2_stagger, p2_relics, p2_timed_out)],
             ) = players.get_many_mut([game.player_one, game.player_two])
             else {
                 continue;
             };
+            if let Some(mut throw_timer) = throw_timer {
+                throw_timer.timer.reset();
+            }
 
             let p1_enchantment = p1_elements.get_enchantment(**p1_guess);
             let p2_enchantment = p2_elements.get_enchantment(**p2_guess);
             let p1_augmentation = p1_elements.get_augmentation(**p1_guess);
             let p2_augmentation = p2_elements.get_augmentation(**p2_guess);
-            let p1_action = Action::new(
+            let mut p1_action = Action::new(
                 **p1_guess,
                 p1_enchantment.cloned(),
                 p1_augmentation.cloned(),
             );
-            let p2_action = Action::new(
+            let mut p2_action = Action::new(
                 **p2_guess,
                 p2_enchantment.cloned(),
                 p2_augmentation.cloned(),
             );
+            if let Some(mutators) = mutators {
+                mutators.modify_action(&mut p1_action, &mut **rng);
+                mutators.modify_action(&mut p2_action, &mut **rng);
+            }
 
-            let outcome = Outcome::new(
+            let mut outcome = Outcome::new(
                 table, p1_action, p1_combo, p1_stagger, p2_action, p2_combo, p2_stagger,
             );
+            if let Some(relics) = p1_relics {
+                relics.modify_outcome(
+                    &RoundContext {
+                        is_first_round: history.num_played() == 0,
+                        had_combo: p1_combo.is_some(),
+                    },
+                    &mut outcome.p1_outcome,
+                    &mut outcome.p2_outcome,
+                );
+            }
+            if let Some(relics) = p2_relics {
+                relics.modify_outcome(
+                    &RoundContext {
+                        is_first_round: history.num_played() == 0,
+                        had_combo: p2_combo.is_some(),
+                    },
+                    &mut outcome.p2_outcome,
+                    &mut outcome.p1_outcome,
+                );
+            }
+
+            if let Some(mutators) = mutators {
+                mutators.modify_outcome(&mut outcome);
+            }
+
+            if outcome.p1_action.guess == outcome.p2_action.guess {
+                let damage = rules.draw_damage as i32;
+                outcome.p1_outcome.damage += damage;
+                outcome.p2_outcome.damage += damage;
+            }
+
+            if let Some(TimedOut { penalty }) = p1_timed_out {
+                outcome.p1_outcome.damage += *penalty as i32;
+                commands.entity(game.player_one).remove::<TimedOut>();
+            }
+            if let Some(TimedOut { penalty }) = p2_timed_out {
+                outcome.p2_outcome.damage += *penalty as i32;
+                commands.entity(game.player_two).remove::<TimedOut>();
+            }
+
+            outcome.p1_outcome.damage = rules.cap_damage(outcome.p1_outcome.damage);
+            outcome.p2_outcome.damage = rules.cap_damage(outcome.p2_outcome.damage);
 
             bevy::log::info!(
                 "{} vs {} =>  I'm hurt {} & enemy hurt {}",
@@ -71,29 +198,152 @@ impl GamePlugin {
                 outcome.p1_outcome.damage,
                 outcome.p2_outcome.damage,
             );
+            let (p1_hp_before, p2_hp_before) = (**p1_hp, **p2_hp);
             **p1_hp = p1_hp.saturating_add_signed(-outcome.p1_outcome.damage);
             **p2_hp = p2_hp.saturating_add_signed(-outcome.p2_outcome.damage);
             commands.entity(game.player_one).remove::<Guess>();
             commands.entity(game.player_two).remove::<Guess>();
+
+            let tiebreak = Some(rules.tiebreak)
+                .filter(|tiebreak| **p1_hp == 0 && **p2_hp == 0 && tiebreak.continues_game());
+            if let Some(tiebreak) = tiebreak {
+                commands.entity(game_entity).insert(Tiebroken(tiebreak));
+            } else {
+                commands.entity(game_entity).remove::<Tiebroken>();
+            }
+            match tiebreak {
+                Some(Tiebreak::SuddenDeath) => {
+                    **p1_hp = 1;
+                    **p2_hp = 1;
+                }
+                Some(Tiebreak::ReplayRound) => {
+                    **p1_hp = p1_hp_before;
+                    **p2_hp = p2_hp_before;
+                }
+                _ => {}
+            }
+            let is_final_round = rules.is_out_of_rounds(history.num_played() + 1);
+            if let Some(RoundLimit {
+                tiebreak: Tiebreak::SuddenDeath,
+                ..
+            }) = rules.round_limit
+            {
+                if is_final_round && **p1_hp == **p2_hp && **p1_hp > 0 {
+                    **p1_hp = 1;
+                    **p2_hp = 1;
+                }
+            }
+            let mut round = RoundRecord::new(&outcome, **p1_hp, **p2_hp);
+            round.replayed = tiebreak == Some(Tiebreak::ReplayRound);
+            history.push(round.clone());
+            if round.replayed {
+                // the round does not count, so combos and staggers are left as they were
+                commands.entity(game_entity).insert(LastOutcome(outcome));
+                commands.trigger(RoundResolved {
+                    game: game_entity,
+                    round,
+                });
+                continue;
+            }
+
+            // losing a throw breaks the combo, as described by `Augmentation::Combo`
             if let Some(Combo) = outcome.p1_outcome.next_combo {
                 commands.entity(game.player_one).insert(Combo);
+            } else {
+                commands.entity(game.player_one).remove::<Combo>();
             }
             if let Some(Combo) = outcome.p2_outcome.next_combo {
                 commands.entity(game.player_two).insert(Combo);
+            } else {
+                commands.entity(game.player_two).remove::<Combo>();
             }
+            // a stagger lasts for a single round
+            commands
+                .entity(game.player_one)
+                .remove::<(Stagger, ForcedAction)>();
+            commands
+                .entity(game.player_two)
+                .remove::<(Stagger, ForcedAction)>();
             if let Some(Stagger) = outcome.p1_outcome.next_stagger {
-                commands.entity(game.player_one).insert(p1_guess.clone());
+                commands
+                    .entity(game.player_one)
+                    .insert((Stagger, ForcedAction(**p1_guess)));
             }
             if let Some(Stagger) = outcome.p2_outcome.next_stagger {
-                commands.entity(game.player_two).insert(p2_guess.clone());
+                commands
+                    .entity(game.player_two)
+                    .insert((Stagger, ForcedAction(**p2_guess)));
             }
+            Self::announce_player_outcome(
+                &mut commands,
+                game_entity,
+                [game.player_one, game.player_two],
+                p1_combo.is_some(),
+                &outcome.p1_outcome,
+                **p1_guess,
+            );
+            Self::announce_player_outcome(
+                &mut commands,
+                game_entity,
+                [game.player_two, game.player_one],
+                p2_combo.is_some(),
+                &outcome.p2_outcome,
+                **p2_guess,
+            );
             commands.entity(game_entity).insert(LastOutcome(outcome));
+            commands.trigger(RoundResolved {
+                game: game_entity,
+                round,
+            });
+        }
+    }
+
+    /// Triggers the events describing what happened to `player` this round.
+    fn announce_player_outcome(
+        commands: &mut Commands,
+        game: Entity,
+        [player, opponent]: [Entity; 2],
+        had_combo: bool,
+        outcome: &PlayerOutcome,
+        guess: Element,
+    ) {
+        match outcome.damage.cmp(&0) {
+            Ordering::Greater => commands.trigger(DamageDealt {
+                game,
+                source: opponent,
+                target: player,
+                amount: outcome.damage.unsigned_abs(),
+            }),
+            Ordering::Less => commands.trigger(Healed {
+                game,
+                player,
+                amount: outcome.damage.unsigned_abs(),
+            }),
+            Ordering::Equal => {}
+        }
+        match (had_combo, outcome.next_combo.is_some()) {
+            (false, true) => commands.trigger(ComboGained { game, player }),
+            (true, false) => commands.trigger(ComboLost { game, player }),
+            _ => {}
+        }
+        if outcome.next_stagger.is_some() {
+            commands.trigger(Staggered {
+                game,
+                player,
+                forced_element: guess,
+            });
         }
     }
 
+    /// Ends games once a player falls or the round limit is reached.
+    /// Games mirrored from a server are only ever ended by the server.
+    #[allow(clippy::type_complexity)]
     fn detect_game_over(
         mut commands: Commands,
-        games: Query<(Entity, &Game), Without<GameOver>>,
+        games: Query<
+            (Entity, &Game, &GameRules, &RoundHistory),
+            (Without<GameOver>, Without<RemoteGame>),
+        >,
         players: Query<&Health, With<Player>>,
     ) {
         for (
@@ -102,20 +352,43 @@ impl GamePlugin {
                 player_one,
                 player_two,
             },
+            rules,
+            history,
         ) in &games
         {
             let Ok([p1_hp, p2_hp]) = players.get_many([*player_one, *player_two]) else {
                 continue;
             };
             let game_over = match (**p1_hp, **p2_hp) {
+                (0, 0) if rules.tiebreak == Tiebreak::PlayerWins => GameOver::Winner(*player_one),
                 (0, 0) => GameOver::Draw,
                 (0, _) => GameOver::Winner(*player_two),
                 (_, 0) => GameOver::Winner(*player_one),
-                (_, _) => {
-                    continue;
+                (p1_hp, p2_hp) => {
+                    let Some(round_limit) = rules.round_limit else {
+                        continue;
+                    };
+                    if !rules.is_out_of_rounds(history.num_played()) {
+                        continue;
+                    }
+                    // out of rounds, so whoever has more health left wins
+                    match (p1_hp.cmp(&p2_hp), round_limit.tiebreak) {
+                        (Ordering::Greater, _) => GameOver::Winner(*player_one),
+                        (Ordering::Less, _) => GameOver::Winner(*player_two),
+                        (Ordering::Equal, Tiebreak::Draw) => GameOver::Draw,
+                        (Ordering::Equal, Tiebreak::PlayerWins) => GameOver::Winner(*player_one),
+                        // play on until one player is ahead
+                        (Ordering::Equal, Tiebreak::SuddenDeath | Tiebreak::ReplayRound) => {
+                            continue;
+                        }
+                    }
                 }
             };
             commands.entity(game).insert(game_over);
+            commands.trigger(GameEnded {
+                game,
+                result: game_over,
+            });
         }
     }
 }
@@ -124,13 +397,15 @@ impl GamePlugin {
 #[derive(SystemSet)]
 pub struct GameSystems;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 #[derive(Event)]
 pub struct SpawnGame {
     pub game: Option<Entity>,
     pub player_one: Option<Entity>,
     pub player_two: Option<Entity>,
     pub table: Option<ElementTable>,
+    pub rules: Option<GameRules>,
+    pub mutators: Option<Mutators>,
 }
 
 impl SpawnGame {
@@ -155,27 +430,257 @@ impl SpawnGame {
         if let Some(table) = &trigger.event().table {
             commands.entity(game).insert(table.clone());
         }
-        commands.entity(player_one).insert((Player, InGame(game)));
-        commands.entity(player_two).insert((Player, InGame(game)));
+        if let Some(mutators) = &trigger.event().mutators {
+            commands.entity(game).insert(mutators.clone());
+        }
+        let rules = trigger.event().rules.clone().unwrap_or_default();
+        if let Some(throw_timer) = rules.throw_timer {
+            commands.entity(game).insert(ThrowTimer::new(throw_timer));
+        }
+        // staggers never carry over from an earlier game
+        for (player, starting_health) in [
+            (player_one, rules.starting_health.0),
+            (player_two, rules.starting_health.1),
+        ] {
+            commands
+                .entity(player)
+                .insert((Player, InGame(game)))
+                .remove::<(Stagger, ForcedAction)>();
+            if let Some(hp) = starting_health {
+                commands.entity(player).insert(Health::new(hp));
+            }
+        }
+        commands.entity(game).insert(rules);
     }
 }
 
 #[derive(Component)]
-#[require(ElementTable, Name(|| Name::new("Game")))]
+#[require(ElementTable, RoundHistory, GameRules, Name(|| Name::new("Game")))]
 pub struct Game {
     pub player_one: Entity,
     pub player_two: Entity,
 }
 
+impl Game {
+    pub fn player(&self, seat: Seat) -> Entity {
+        match seat {
+            Seat::PlayerOne => self.player_one,
+            Seat::PlayerTwo => self.player_two,
+        }
+    }
+
+    pub fn seat(&self, player: Entity) -> Option<Seat> {
+        if player == self.player_one {
+            Some(Seat::PlayerOne)
+        } else if player == self.player_two {
+            Some(Seat::PlayerTwo)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Deref)]
 #[derive(Component)]
 pub struct InGame(pub Entity);
 
+/// The rules a game is played under, attached by [`SpawnGame`].
+#[derive(Clone, Debug, Default)]
+#[derive(Deserialize, Serialize)]
+#[derive(Component)]
+#[serde(default)]
+pub struct GameRules {
+    /// The health player one and player two start the game with.
+    /// A player keeps their current health when `None`.
+    pub starting_health: (Option<u32>, Option<u32>),
+    pub round_limit: Option<RoundLimit>,
+    /// The most damage a player can take in a single round.
+    pub damage_cap: Option<u32>,
+    /// Chip damage dealt to both players when they throw the same element.
+    pub draw_damage: u32,
+    /// How a round in which both players fall is resolved.
+    pub tiebreak: Tiebreak,
+    /// An optional countdown for each throw.
+    pub throw_timer: Option<ThrowTimerRules>,
+}
+
+impl GameRules {
+    pub fn cap_damage(&self, damage: i32) -> i32 {
+        match self.damage_cap {
+            Some(cap) => damage.min(cap as i32),
+            None => damage,
+        }
+    }
+
+    /// Whether the game has run out of rounds once `rounds` have been played.
+    pub fn is_out_of_rounds(&self, rounds: usize) -> bool {
+        self.round_limit
+            .is_some_and(|round_limit| rounds >= round_limit.max_rounds as usize)
+    }
+}
+
+/// Ends the game after a number of rounds, with the player on more health winning.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct RoundLimit {
+    pub max_rounds: u16,
+    /// How the game is decided when both players are on equal health.
+    /// SuddenDeath drops both players to 1 HP, and ReplayRound plays on until one is ahead.
+    pub tiebreak: Tiebreak,
+}
+
+/// How a round in which both players fall to 0 HP is resolved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Deserialize, Serialize)]
+pub enum Tiebreak {
+    /// The game ends in a draw.
+    #[default]
+    Draw,
+    /// Both players continue at 1 HP.
+    SuddenDeath,
+    /// Player one wins. In a campaign, this is the player.
+    PlayerWins,
+    /// Both players return to their health before the round, which is thrown again.
+    ReplayRound,
+}
+
+impl Tiebreak {
+    /// Whether the game goes on after both players fall.
+    pub fn continues_game(&self) -> bool {
+        matches!(self, Tiebreak::SuddenDeath | Tiebreak::ReplayRound)
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            Tiebreak::Draw => "Both players fell. It's a draw!",
+            Tiebreak::SuddenDeath => "Both players fell. Sudden death at 1 HP!",
+            Tiebreak::PlayerWins => "Both players fell. The player wins the tie!",
+            Tiebreak::ReplayRound => "Both players fell. The round will be replayed!",
+        }
+    }
+}
+
+/// A countdown for each round, after which players who have not thrown throw automatically.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct ThrowTimerRules {
+    pub seconds: f32,
+    pub on_timeout: AutoThrow,
+}
+
+/// The throw made for a player who runs out of time.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum AutoThrow {
+    /// A random element.
+    Random,
+    /// The same element as last round, or a random one in the first round.
+    RepeatLast,
+    /// A random element, and the player takes this much extra damage.
+    Penalty(u32),
+}
+
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct ThrowTimer {
+    pub rules: ThrowTimerRules,
+    /// Restarts whenever a round is resolved.
+    pub timer: Timer,
+}
+
+impl ThrowTimer {
+    pub fn new(rules: ThrowTimerRules) -> Self {
+        Self {
+            rules,
+            timer: Timer::from_seconds(rules.seconds, TimerMode::Once),
+        }
+    }
+
+    pub fn seconds_left(&self) -> u32 {
+        self.timer.remaining_secs().ceil() as u32
+    }
+}
+
+/// The only element a player may throw next round.
+#[derive(Clone, Copy, Debug)]
+#[derive(Deref)]
+#[derive(Component)]
+pub struct ForcedAction(pub Element);
+
+/// Marks a player whose throw was made for them with a penalty.
+#[derive(Component)]
+pub struct TimedOut {
+    pub penalty: u32,
+}
+
+/// Present on a game when a tiebreak was applied in the last round.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct Tiebroken(pub Tiebreak);
+
 #[derive(Component)]
 pub struct LastOutcome(pub Outcome);
 
+#[derive(Clone, Copy, Debug)]
 #[derive(Component)]
 pub enum GameOver {
     Winner(Entity),
     Draw,
 }
+
+/// A round of `game` has been resolved and added to its [`RoundHistory`].
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct RoundResolved {
+    pub game: Entity,
+    pub round: RoundRecord,
+}
+
+/// `target` lost health to `source` during a round.
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct DamageDealt {
+    pub game: Entity,
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: u32,
+}
+
+/// `player` regained health during a round.
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct Healed {
+    pub game: Entity,
+    pub player: Entity,
+    pub amount: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct ComboGained {
+    pub game: Entity,
+    pub player: Entity,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct ComboLost {
+    pub game: Entity,
+    pub player: Entity,
+}
+
+/// `player` must throw `forced_element` again next round.
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct Staggered {
+    pub game: Entity,
+    pub player: Entity,
+    pub forced_element: Element,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct GameEnded {
+    pub game: Entity,
+    pub result: GameOver,
+}
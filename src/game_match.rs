@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::{
+    App, Commands, Component, Entity, Event, Name, Plugin, Query, Trigger, With, Without,
+};
+
+use crate::{Combo, ForcedAction, GameEnded, GameOver, Health, Player, SpawnGame, Stagger};
+
+pub struct MatchPlugin;
+
+impl Plugin for MatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(SpawnMatch::observer);
+        app.add_observer(Self::advance_matches);
+    }
+}
+
+impl MatchPlugin {
+    /// Counts the result of each finished game and either starts the next game or ends the match.
+    fn advance_matches(
+        trigger: Trigger<GameEnded>,
+        mut commands: Commands,
+        mut matches: Query<(Entity, &mut Match), Without<GameOver>>,
+        mut players: Query<&mut Health, With<Player>>,
+    ) {
+        let GameEnded { game, result } = *trigger.event();
+        let Some((match_entity, mut game_match)) = matches
+            .iter_mut()
+            .find(|(_, game_match)| game_match.current_game == Some(game))
+        else {
+            return;
+        };
+
+        match result {
+            GameOver::Winner(winner) if winner == game_match.player_one => game_match.wins.0 += 1,
+            GameOver::Winner(_) => game_match.wins.1 += 1,
+            // drawn games are replayed, up to the match's limit on draws
+            GameOver::Draw => game_match.draws += 1,
+        }
+
+        if let Some(result) = game_match.result() {
+            game_match.current_game = None;
+            commands.entity(match_entity).insert(result);
+            commands.trigger(MatchEnded {
+                game_match: match_entity,
+                result,
+                wins: game_match.wins,
+            });
+            return;
+        }
+
+        commands.entity(game).despawn();
+        let players_with_health = [
+            (game_match.player_one, game_match.starting_health.0.clone()),
+            (game_match.player_two, game_match.starting_health.1.clone()),
+        ];
+        for (player, starting_health) in players_with_health {
+            if game_match.rules.reset_health {
+                if let Ok(mut health) = players.get_mut(player) {
+                    *health = starting_health;
+                }
+            }
+            if game_match.rules.reset_combo {
+                commands
+                    .entity(player)
+                    .remove::<(Combo, Stagger, ForcedAction)>();
+            }
+        }
+        game_match.current_game = Some(game_match.spawn_game(&mut commands));
+    }
+}
+
+/// How a [`Match`] is played.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchRules {
+    /// The match ends once a player has won a majority of this many games.
+    pub best_of: u32,
+    /// Whether both players return to their starting health for each game.
+    pub reset_health: bool,
+    /// Whether Combo and Stagger are cleared between games.
+    pub reset_combo: bool,
+    /// The match ends once this many games have been drawn, won by whoever has more wins.
+    pub max_draws: u32,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            best_of: 3,
+            reset_health: true,
+            reset_combo: true,
+            max_draws: 3,
+        }
+    }
+}
+
+impl MatchRules {
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+}
+
+/// A series of games between the same two players.
+#[derive(Component)]
+#[require(Name(|| Name::new("Match")))]
+pub struct Match {
+    pub player_one: Entity,
+    pub player_two: Entity,
+    pub rules: MatchRules,
+    /// Games won by player one and player two.
+    pub wins: (u32, u32),
+    pub draws: u32,
+    /// The game being played, until the match is over.
+    pub current_game: Option<Entity>,
+    starting_health: (Health, Health),
+    game_template: SpawnGame,
+}
+
+impl Match {
+    /// The result of the match, once a player has won enough games or too many games were drawn.
+    pub fn result(&self) -> Option<GameOver> {
+        let wins_needed = self.rules.wins_needed();
+        if self.wins.0 >= wins_needed {
+            Some(GameOver::Winner(self.player_one))
+        } else if self.wins.1 >= wins_needed {
+            Some(GameOver::Winner(self.player_two))
+        } else if self.draws >= self.rules.max_draws {
+            Some(match self.wins.0.cmp(&self.wins.1) {
+                Ordering::Greater => GameOver::Winner(self.player_one),
+                Ordering::Less => GameOver::Winner(self.player_two),
+                Ordering::Equal => GameOver::Draw,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn games_played(&self) -> u32 {
+        self.wins.0 + self.wins.1 + self.draws
+    }
+
+    fn spawn_game(&self, commands: &mut Commands) -> Entity {
+        let game = commands.spawn_empty().id();
+        commands.trigger(SpawnGame {
+            game: Some(game),
+            player_one: Some(self.player_one),
+            player_two: Some(self.player_two),
+            ..self.game_template.clone()
+        });
+        game
+    }
+}
+
+/// Starts a best-of-N match between two players, spawning its first game.
+/// Both players should already have their starting [`Health`].
+#[derive(Default)]
+#[derive(Event)]
+pub struct SpawnMatch {
+    pub player_one: Option<Entity>,
+    pub player_two: Option<Entity>,
+    pub rules: MatchRules,
+    /// The settings used for every game of the match. Its players are ignored.
+    pub game: SpawnGame,
+}
+
+impl SpawnMatch {
+    fn observer(trigger: Trigger<Self>, mut commands: Commands, players: Query<&Health>) {
+        let player_one = trigger
+            .event()
+            .player_one
+            .unwrap_or_else(|| commands.spawn_empty().id());
+        let player_two = trigger
+            .event()
+            .player_two
+            .unwrap_or_else(|| commands.spawn_empty().id());
+        let starting_health = |player| players.get(player).cloned().unwrap_or(Health::PLAYER_MAX);
+
+        let mut game_match = Match {
+            player_one,
+            player_two,
+            rules: trigger.event().rules,
+            wins: (0, 0),
+            draws: 0,
+            current_game: None,
+            starting_health: (starting_health(player_one), starting_health(player_two)),
+            game_template: trigger.event().game.clone(),
+        };
+        game_match.current_game = Some(game_match.spawn_game(&mut commands));
+        commands.spawn(game_match);
+    }
+}
+
+/// The final game of the match is left for whoever started the match to clean up.
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct MatchEnded {
+    pub game_match: Entity,
+    pub result: GameOver,
+    pub wins: (u32, u32),
+}
@@ -0,0 +1,351 @@
+use std::collections::BTreeMap;
+
+use derive_more::derive::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::{
+    resource_changed, Added, App, Changed, Commands, Component, Entity, IntoSystemConfigs, Plugin,
+    Query, Res, ResMut, Resource, Single, Trigger, Update,
+};
+
+use crate::{
+    storage, Action, Ascension, Bonus, BonusApplied, Campaign, CampaignSystems, ChooseEvolution,
+    Element, ElementTable, Encounter, Endless, Game, GameSystems, Level, Mutators, Phase,
+    PlayerClass, PlayerElements, Round, RoundResolved, RunSeed,
+};
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunHistory::load());
+        app.add_observer(Self::record_bonus);
+        app.add_observer(Self::record_evolution);
+        app.add_observer(Self::record_throw);
+        app.add_systems(
+            Update,
+            (
+                Self::start_runs.after(GameSystems).before(CampaignSystems),
+                Self::finish_runs.after(CampaignSystems),
+                Self::save_history.run_if(resource_changed::<RunHistory>),
+            ),
+        );
+    }
+}
+
+impl HistoryPlugin {
+    fn start_runs(
+        mut commands: Commands,
+        campaigns: Query<(Entity, &RunSeed, &PlayerClass, &Ascension, &Mutators), Added<Campaign>>,
+    ) {
+        for (campaign, seed, player_class, ascension, mutators) in &campaigns {
+            commands.entity(campaign).insert(RunRecord::new(
+                **seed,
+                *player_class,
+                *ascension,
+                mutators.clone(),
+            ));
+        }
+    }
+
+    fn record_throw(
+        trigger: Trigger<RoundResolved>,
+        games: Query<&Game>,
+        mut campaigns: Query<(&Campaign, &Round, &Level, &Encounter, &mut RunRecord)>,
+    ) {
+        let RoundResolved {
+            game,
+            round: resolved,
+        } = trigger.event();
+        let Ok(game) = games.get(*game) else {
+            return;
+        };
+        if resolved.replayed {
+            return;
+        }
+        for (Campaign { player }, round, level, encounter, mut record) in &mut campaigns {
+            let Some(seat) = game.seat(*player) else {
+                continue;
+            };
+            let (player_side, enemy_side) = (resolved.side(seat), resolved.side(seat.opponent()));
+            record.throws.push(ThrowRecord {
+                round: **round,
+                level: *level,
+                encounter: *encounter,
+                player_action: player_side.action.clone(),
+                enemy_action: enemy_side.action.clone(),
+                damage_taken: player_side.damage,
+                damage_dealt: enemy_side.damage,
+            });
+        }
+    }
+
+    fn record_bonus(trigger: Trigger<BonusApplied>, mut record: Single<&mut RunRecord>) {
+        let BonusApplied { bonus, element } = trigger.event();
+        record.bonuses.push((bonus.clone(), *element));
+    }
+
+    fn record_evolution(trigger: Trigger<ChooseEvolution>, mut record: Single<&mut RunRecord>) {
+        record.evolutions.push(trigger.event().element);
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn finish_runs(
+        campaigns: Query<
+            (
+                &Campaign,
+                &Phase,
+                &Round,
+                &Level,
+                &Encounter,
+                &ElementTable,
+                &RunRecord,
+                Option<&Endless>,
+            ),
+            Changed<Phase>,
+        >,
+        players: Query<&PlayerElements>,
+        mut history: ResMut<RunHistory>,
+    ) {
+        for (Campaign { player }, phase, round, level, encounter, table, record, endless) in
+            &campaigns
+        {
+            let result = match phase {
+                Phase::Victory => RunResult::Victory,
+                Phase::GameOver => RunResult::Defeat(CauseOfDeath {
+                    level: *level,
+                    encounter: *encounter,
+                    final_throw: record.throws.last().cloned(),
+                }),
+                Phase::InGame | Phase::ProvidingBonus => continue,
+            };
+            let mut record = record.clone();
+            record.result = Some(result);
+            record.rounds = **round;
+            record.endless_round = endless.map(|_| **round);
+            record.final_elements = players.get(*player).ok().cloned();
+            record.final_table = Some(table.clone());
+            history.record(record);
+        }
+    }
+
+    fn save_history(history: Res<RunHistory>) {
+        history.save();
+    }
+}
+
+/// Everything that happened during a single campaign run.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+#[derive(Component)]
+pub struct RunRecord {
+    pub seed: u64,
+    pub player_class: PlayerClass,
+    pub ascension: Ascension,
+    /// Runs recorded before mutators existed had none.
+    #[serde(default)]
+    pub mutators: Mutators,
+    pub rounds: u16,
+    pub endless_round: Option<u16>,
+    pub bonuses: Vec<(Bonus, Element)>,
+    pub evolutions: Vec<Element>,
+    pub throws: Vec<ThrowRecord>,
+    pub final_elements: Option<PlayerElements>,
+    pub final_table: Option<ElementTable>,
+    /// Empty until the run ends.
+    pub result: Option<RunResult>,
+}
+
+impl RunRecord {
+    pub fn new(
+        seed: u64,
+        player_class: PlayerClass,
+        ascension: Ascension,
+        mutators: Mutators,
+    ) -> Self {
+        Self {
+            seed,
+            player_class,
+            ascension,
+            mutators,
+            rounds: 0,
+            endless_round: None,
+            bonuses: Vec::default(),
+            evolutions: Vec::default(),
+            throws: Vec::default(),
+            final_elements: None,
+            final_table: None,
+            result: None,
+        }
+    }
+
+    pub fn is_victory(&self) -> bool {
+        // a run continued in endless mode still counts as a win
+        matches!(self.result, Some(RunResult::Victory)) || self.endless_round.is_some()
+    }
+
+    pub fn is_ranked(&self) -> bool {
+        self.mutators.is_ranked()
+    }
+}
+
+/// A single throw from the campaign player's point of view.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct ThrowRecord {
+    pub round: u16,
+    pub level: Level,
+    pub encounter: Encounter,
+    pub player_action: Action,
+    pub enemy_action: Action,
+    pub damage_taken: i32,
+    pub damage_dealt: i32,
+}
+
+impl ThrowRecord {
+    pub fn is_win(&self) -> bool {
+        self.damage_dealt > self.damage_taken
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum RunResult {
+    Victory,
+    Defeat(CauseOfDeath),
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct CauseOfDeath {
+    pub level: Level,
+    pub encounter: Encounter,
+    pub final_throw: Option<ThrowRecord>,
+}
+
+impl std::fmt::Display for CauseOfDeath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let enemy = match self.encounter {
+            Encounter::Battle => "an enemy",
+            Encounter::Elite => "an elite",
+            Encounter::Boss => "the boss",
+        };
+        write!(f, "Defeated by {enemy} at level {:?}", self.level)?;
+        if let Some(throw) = &self.final_throw {
+            write!(
+                f,
+                " ({} vs {})",
+                throw.player_action.guess, throw.enemy_action.guess
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Finished runs, most recent last.
+#[derive(Clone, Debug, Default)]
+#[derive(Deref, DerefMut)]
+#[derive(Deserialize, Serialize)]
+#[derive(Resource)]
+pub struct RunHistory(Vec<RunRecord>);
+
+impl RunHistory {
+    const STORAGE_KEY: &str = "history";
+    pub const MAX_RUNS: usize = 100;
+
+    pub fn load() -> Self {
+        storage::load(Self::STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(Self::STORAGE_KEY, self);
+    }
+
+    /// Adds a finished run. A run continued in endless mode replaces its earlier record.
+    pub fn record(&mut self, record: RunRecord) {
+        self.0.retain(|existing| existing.seed != record.seed);
+        self.0.push(record);
+        let excess = self.0.len().saturating_sub(Self::MAX_RUNS);
+        self.0.drain(..excess);
+    }
+
+    pub fn stats(&self) -> RunStats {
+        let mut stats = RunStats::default();
+        for record in &self.0 {
+            stats.runs += 1;
+            stats.total_rounds += u32::from(record.rounds);
+            let class_stats = stats.classes.entry(record.player_class).or_default();
+            class_stats.0 += 1;
+            if record.is_victory() {
+                stats.wins += 1;
+                class_stats.1 += 1;
+            }
+            for (bonus, _) in &record.bonuses {
+                *stats.bonuses.entry(bonus.get_readable_name()).or_default() += 1;
+            }
+            for throw in &record.throws {
+                let element_stats = stats.elements.entry(throw.player_action.guess).or_default();
+                element_stats.0 += 1;
+                if throw.is_win() {
+                    element_stats.1 += 1;
+                }
+            }
+        }
+        stats
+    }
+}
+
+/// Aggregate statistics over every recorded run.
+#[derive(Clone, Debug, Default)]
+pub struct RunStats {
+    pub runs: u32,
+    pub wins: u32,
+    pub total_rounds: u32,
+    /// Runs and wins for each class.
+    pub classes: BTreeMap<PlayerClass, (u32, u32)>,
+    /// How often each bonus was picked, by name.
+    pub bonuses: BTreeMap<String, u32>,
+    /// Throws and won throws for each element.
+    pub elements: BTreeMap<Element, (u32, u32)>,
+}
+
+impl RunStats {
+    pub fn win_rate(&self) -> f32 {
+        if self.runs == 0 {
+            0.
+        } else {
+            self.wins as f32 / self.runs as f32
+        }
+    }
+
+    pub fn average_rounds(&self) -> f32 {
+        if self.runs == 0 {
+            0.
+        } else {
+            self.total_rounds as f32 / self.runs as f32
+        }
+    }
+
+    /// The element thrown most often.
+    pub fn favorite_element(&self) -> Option<Element> {
+        self.elements
+            .iter()
+            .max_by_key(|(_, (throws, _))| *throws)
+            .map(|(element, _)| *element)
+    }
+
+    /// The share of throws of each element which won, for every element thrown at least once.
+    pub fn element_win_rates(&self) -> impl Iterator<Item = (Element, f32)> + '_ {
+        self.elements
+            .iter()
+            .filter(|(_, (throws, _))| *throws > 0)
+            .map(|(element, (throws, wins))| (*element, *wins as f32 / *throws as f32))
+    }
+
+    pub fn favorite_bonus(&self) -> Option<&str> {
+        self.bonuses
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(name, _)| name.as_str())
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::prelude::{
+    App, Commands, Component, Event, IntoSystemConfigs, Name, Plugin, Query, Resource, Trigger,
+    Update, With, Without,
+};
+
+use crate::{
+    Augmentation, Element, Game, GameEnded, GameOver, GameRules, GameSystems, Guess,
+    PlayerElements, Seat, SpawnGame,
+};
+
+pub struct HotseatPlugin;
+
+impl Plugin for HotseatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HotseatSetup>();
+        app.add_observer(SpawnHotseat::observer);
+        app.add_observer(Self::despawn_finished_games);
+        app.add_systems(Update, Self::pass_turns.after(GameSystems));
+    }
+}
+
+impl HotseatPlugin {
+    /// Hands the device to the other player once the player whose turn it is has thrown.
+    fn pass_turns(
+        mut games: Query<(&Game, &mut Hotseat), Without<GameOver>>,
+        guesses: Query<(), With<Guess>>,
+    ) {
+        for (game, mut hotseat) in &mut games {
+            let current = game.player(hotseat.turn);
+            let other = game.player(hotseat.turn.opponent());
+            if guesses.contains(current) && !guesses.contains(other) {
+                hotseat.turn = hotseat.turn.opponent();
+            }
+        }
+    }
+
+    fn despawn_finished_games(
+        trigger: Trigger<GameEnded>,
+        mut commands: Commands,
+        games: Query<&Game, With<Hotseat>>,
+    ) {
+        let game_entity = trigger.event().game;
+        let Ok(game) = games.get(game_entity) else {
+            return;
+        };
+        commands.entity(game.player_one).despawn();
+        commands.entity(game.player_two).despawn();
+        commands.entity(game_entity).despawn();
+    }
+}
+
+/// A game between two people sharing one device.
+/// Players throw in turn, and the device is passed between them so neither sees the other's throw.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct Hotseat {
+    /// The player who is holding the device. After a round is resolved,
+    /// the player who threw last throws first in the next round.
+    pub turn: Seat,
+}
+
+/// The elements each player brings to the next hotseat game.
+#[derive(Clone, Debug)]
+#[derive(Resource)]
+pub struct HotseatSetup {
+    pub player_one: PlayerElements,
+    pub player_two: PlayerElements,
+    pub rules: GameRules,
+}
+
+impl Default for HotseatSetup {
+    fn default() -> Self {
+        Self {
+            player_one: PlayerElements::rock_paper_scissors(),
+            player_two: PlayerElements::rock_paper_scissors(),
+            rules: GameRules::default(),
+        }
+    }
+}
+
+impl HotseatSetup {
+    pub fn elements(&self, seat: Seat) -> &PlayerElements {
+        match seat {
+            Seat::PlayerOne => &self.player_one,
+            Seat::PlayerTwo => &self.player_two,
+        }
+    }
+
+    /// Cycles `element` for `seat` from missing, to plain, through each augmentation, and back to missing.
+    pub fn cycle_element(&mut self, seat: Seat, element: Element) {
+        let elements = match seat {
+            Seat::PlayerOne => &mut self.player_one,
+            Seat::PlayerTwo => &mut self.player_two,
+        };
+        if !elements.elements.contains(&element) {
+            elements.insert(element);
+            return;
+        }
+        let next_augmentation = match elements.augmentations.get(&element) {
+            None => Some(Augmentation::ALL[0]),
+            Some(augmentation) => Augmentation::ALL
+                .iter()
+                .skip_while(|other| *other != augmentation)
+                .nth(1)
+                .copied(),
+        };
+        match next_augmentation {
+            Some(augmentation) => {
+                if let Err(error) = elements.augment(element, augmentation) {
+                    bevy::log::warn!("Failed to augment hotseat element: {error}");
+                }
+            }
+            None => elements.remove(element),
+        }
+    }
+
+    /// Both players need enough elements to choose from.
+    pub fn is_ready(&self) -> bool {
+        [&self.player_one, &self.player_two]
+            .iter()
+            .all(|elements| elements.elements.len() >= PlayerElements::MIN_ELEMENTS)
+    }
+}
+
+/// Starts a hotseat game with fresh players, player one holding the device.
+#[derive(Clone, Default)]
+#[derive(Event)]
+pub struct SpawnHotseat {
+    pub player_one: PlayerElements,
+    pub player_two: PlayerElements,
+    pub rules: GameRules,
+}
+
+impl From<&HotseatSetup> for SpawnHotseat {
+    fn from(setup: &HotseatSetup) -> Self {
+        Self {
+            player_one: setup.player_one.clone(),
+            player_two: setup.player_two.clone(),
+            rules: setup.rules.clone(),
+        }
+    }
+}
+
+impl SpawnHotseat {
+    fn observer(trigger: Trigger<Self>, mut commands: Commands) {
+        let SpawnHotseat {
+            player_one,
+            player_two,
+            rules,
+        } = trigger.event();
+        let player_one = commands
+            .spawn((Name::new(Seat::PlayerOne.to_string()), player_one.clone()))
+            .id();
+        let player_two = commands
+            .spawn((Name::new(Seat::PlayerTwo.to_string()), player_two.clone()))
+            .id();
+        let game = commands
+            .spawn(Hotseat {
+                turn: Seat::PlayerOne,
+            })
+            .id();
+        commands.trigger(SpawnGame {
+            game: Some(game),
+            player_one: Some(player_one),
+            player_two: Some(player_two),
+            rules: Some(rules.clone()),
+            ..Default::default()
+        });
+    }
+}
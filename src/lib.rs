@@ -1,17 +1,61 @@
 use bevy::app::{PluginGroup, PluginGroupBuilder};
 
+mod achievement;
+pub use achievement::*;
+
+mod ascension;
+pub use ascension::*;
+
+mod bonus;
+pub use bonus::*;
+
 mod campaign;
 pub use campaign::*;
 
+mod campaign_definition;
+pub use campaign_definition::*;
+
+mod class;
+pub use class::*;
+
+mod data;
+pub use data::*;
+
 mod game;
 pub use game::*;
 
+mod game_match;
+pub use game_match::*;
+
+mod history;
+pub use history::*;
+
+mod hotseat;
+pub use hotseat::*;
+
+mod mutator;
+pub use mutator::*;
+
+mod online;
+pub use online::*;
+
 mod player;
 pub use player::*;
 
+mod profile;
+pub use profile::*;
+
+mod relic;
+pub use relic::*;
+
+mod round_history;
+pub use round_history::*;
+
 mod rps;
 pub use rps::*;
 
+mod storage;
+
 mod ui;
 
 pub struct RockPaperParadisePlugins;
@@ -20,8 +64,15 @@ impl PluginGroup for RockPaperParadisePlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
             .add(RpsPlugin)
+            .add(ProfilePlugin)
+            .add(BonusPlugin)
             .add(CampaignPlugin)
             .add(GamePlugin)
+            .add(MatchPlugin)
+            .add(HotseatPlugin)
+            .add(OnlineClientPlugin)
+            .add(HistoryPlugin)
+            .add(AchievementsPlugin)
             .add(ui::UIPlugin)
     }
 }
@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+
+use derive_more::derive::{Deref, DerefMut, Display};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::Component;
+
+use crate::{Action, Aspect, Augmentation, Element, Outcome, PlayerElements, RewardOffers};
+
+/// An optional run-wide rule change, toggled before starting a campaign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Display)]
+#[derive(Deserialize, Serialize)]
+pub enum Mutator {
+    #[display("Mirror Match")]
+    MirrorMatch,
+    #[display("Armored Enemies")]
+    ArmoredEnemies,
+    #[display("Double Damage")]
+    DoubleDamage,
+    #[display("Random Aspects")]
+    RandomAspects,
+    #[display("Evolutions Only")]
+    EvolutionsOnly,
+}
+
+impl Mutator {
+    pub const ALL: [Self; 5] = [
+        Mutator::MirrorMatch,
+        Mutator::ArmoredEnemies,
+        Mutator::DoubleDamage,
+        Mutator::RandomAspects,
+        Mutator::EvolutionsOnly,
+    ];
+
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            Mutator::MirrorMatch => "Enemies copy your elements and upgrades",
+            Mutator::ArmoredEnemies => "Every enemy element is Armored",
+            Mutator::DoubleDamage => "All damage is doubled",
+            Mutator::RandomAspects => "Throws without an Aspect gain a random one",
+            Mutator::EvolutionsOnly => "No bonuses are offered, only evolutions",
+        }
+    }
+
+    fn upgrade_enemy(&self, enemy_elements: &mut PlayerElements, player_elements: &PlayerElements) {
+        match self {
+            Mutator::MirrorMatch => *enemy_elements = player_elements.clone(),
+            Mutator::ArmoredEnemies => {
+                for element in enemy_elements.elements.clone() {
+                    if let Err(error) = enemy_elements.augment(element, Augmentation::Armored) {
+                        bevy::log::warn!("Failed to armor enemy element: {error}");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reward_offers(&self, offers: RewardOffers) -> RewardOffers {
+        match self {
+            Mutator::EvolutionsOnly => RewardOffers {
+                bonuses: 0,
+                ..offers
+            },
+            _ => offers,
+        }
+    }
+
+    fn modify_action(&self, action: &mut Action, rng: &mut impl RngCore) {
+        // an element is never its own Aspect, and an Aspect the player chose is kept
+        if let (Mutator::RandomAspects, None) = (self, action.enchantment) {
+            action.enchantment = Some(Aspect::new(Element::random_without(rng, action.guess)));
+        }
+    }
+
+    fn modify_outcome(&self, outcome: &mut Outcome) {
+        if let Mutator::DoubleDamage = self {
+            for player_outcome in [&mut outcome.p1_outcome, &mut outcome.p2_outcome] {
+                if player_outcome.damage > 0 {
+                    player_outcome.damage *= 2;
+                }
+            }
+        }
+    }
+}
+
+/// The mutators active for a run. Each hook applies every active mutator in turn.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Deref, DerefMut)]
+#[derive(Deserialize, Serialize)]
+#[derive(Component)]
+pub struct Mutators(pub BTreeSet<Mutator>);
+
+impl Mutators {
+    pub fn toggle(&mut self, mutator: Mutator) {
+        if !self.0.remove(&mutator) {
+            self.0.insert(mutator);
+        }
+    }
+
+    /// Runs with mutators are kept off the normal leaderboards: their wins, ascension unlocks and
+    /// endless rounds are not recorded in the profile. The elements and bonuses they use still
+    /// count towards unlocks, since those track what the player has tried rather than how well.
+    pub fn is_ranked(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn upgrade_enemy(
+        &self,
+        enemy_elements: &mut PlayerElements,
+        player_elements: &PlayerElements,
+    ) {
+        for mutator in &self.0 {
+            mutator.upgrade_enemy(enemy_elements, player_elements);
+        }
+    }
+
+    pub fn reward_offers(&self, offers: RewardOffers) -> RewardOffers {
+        self.0
+            .iter()
+            .fold(offers, |offers, mutator| mutator.reward_offers(offers))
+    }
+
+    pub fn modify_action(&self, action: &mut Action, rng: &mut impl RngCore) {
+        for mutator in &self.0 {
+            mutator.modify_action(action, rng);
+        }
+    }
+
+    pub fn modify_outcome(&self, outcome: &mut Outcome) {
+        for mutator in &self.0 {
+            mutator.modify_outcome(outcome);
+        }
+    }
+}
+
+impl std::fmt::Display for Mutators {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = self.0.iter().map(Mutator::to_string).collect::<Vec<_>>();
+        write!(f, "{}", names.join(", "))
+    }
+}
@@ -0,0 +1,480 @@
+use rand::{rngs::OsRng, RngCore};
+
+use bevy::prelude::{
+    Added, App, Commands, Component, Entity, Event, IntoSystemConfigs, Name, Plugin, Query,
+    Resource, Trigger, Update, With, Without,
+};
+
+use crate::{
+    Combo, Element, ForcedAction, Game, GameEnded, GameOver, GameRules, GameSystems, Guess, Health,
+    InGame, LastOutcome, PlayerElements, RoundHistory, RoundResolved, RoundSide, Seat, SpawnGame,
+    Stagger,
+};
+
+use super::{
+    decode, ClientMessage, Commitment, Connection, Lobby, ProtocolError, RoomCode, ServerMessage,
+    TransportError,
+};
+
+/// Plays online games hosted by a server running [`super::OnlineServerPlugin`].
+/// Each online game is mirrored locally, but its rounds are only ever resolved by the server.
+pub struct OnlineClientPlugin;
+
+impl Plugin for OnlineClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OnlineSettings>();
+        app.add_observer(Self::despawn_finished_games);
+        app.add_systems(
+            Update,
+            (
+                Self::join_games,
+                Self::receive_messages,
+                Self::commit_guesses,
+            )
+                .chain()
+                .before(GameSystems),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, Self::finish_connecting.before(Self::join_games));
+    }
+}
+
+impl OnlineClientPlugin {
+    /// Hands each connection opened on another thread to its client, or reports why it failed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn finish_connecting(
+        mut commands: Commands,
+        mut clients: Query<(Entity, &mut super::PendingConnection), With<OnlineClient>>,
+    ) {
+        for (client, mut pending) in &mut clients {
+            let Some(result) = pending.poll() else {
+                continue;
+            };
+            commands.entity(client).remove::<super::PendingConnection>();
+            match result {
+                Ok(connection) => {
+                    commands.entity(client).insert(connection);
+                }
+                Err(error) => commands.trigger(OnlineConnectionFailed { client, error }),
+            }
+        }
+    }
+
+    /// Joins a lobby through each new connection, or rejoins the game it lost the connection to.
+    fn join_games(mut clients: Query<(&OnlineClient, &mut Connection), Added<Connection>>) {
+        for (client, mut connection) in &mut clients {
+            let message = match (&client.role, client.game, client.session) {
+                (_, Some(_), Some(session)) => ClientMessage::Rejoin { session },
+                (OnlineRole::Player { elements, lobby }, ..) => ClientMessage::Join {
+                    elements: elements.clone(),
+                    lobby: lobby.clone(),
+                },
+                (OnlineRole::Spectator { code }, ..) => {
+                    ClientMessage::Spectate { code: code.clone() }
+                }
+            };
+            send(&mut connection, &message);
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn receive_messages(
+        mut commands: Commands,
+        mut clients: Query<(Entity, &mut Connection, &mut OnlineClient)>,
+        mut games: Query<(Entity, &Game, &RemoteGame, &mut RoundHistory)>,
+        mut players: Query<(&mut Health, Option<&PendingReveal>)>,
+    ) {
+        for (client, mut connection, mut online_client) in &mut clients {
+            loop {
+                let text = match connection.receive() {
+                    Ok(Some(text)) => text,
+                    Ok(None) => break,
+                    Err(error) => {
+                        bevy::log::warn!("Lost connection to the server: {error}");
+                        commands.entity(client).remove::<Connection>();
+                        break;
+                    }
+                };
+                let message = match decode::<ServerMessage>(&text) {
+                    Ok(message) => message,
+                    Err(error) => {
+                        bevy::log::warn!("Ignored a message from the server: {error}");
+                        continue;
+                    }
+                };
+                let game = games
+                    .iter_mut()
+                    .find(|(_, _, remote, _)| remote.connection == client);
+                match message {
+                    ServerMessage::RoomCreated { code } => {
+                        commands.trigger(OnlineRoomCreated { client, code });
+                    }
+                    ServerMessage::Started {
+                        seat,
+                        session,
+                        player_one,
+                        player_two,
+                        rules,
+                        history,
+                    } => {
+                        // a client which rejoined replaces its old copy of the game
+                        if let Some((old_game, game, ..)) = game {
+                            despawn_game(&mut commands, old_game, game);
+                        }
+                        let game = spawn_game(
+                            &mut commands,
+                            client,
+                            Some(seat),
+                            [player_one, player_two],
+                            rules,
+                            history,
+                        );
+                        online_client.game = Some(game);
+                        online_client.session = Some(session);
+                        commands.trigger(OnlineGameStarted {
+                            game,
+                            seat: Some(seat),
+                        });
+                    }
+                    ServerMessage::Spectating {
+                        player_one,
+                        player_two,
+                        rules,
+                        history,
+                    } => {
+                        let game = spawn_game(
+                            &mut commands,
+                            client,
+                            None,
+                            [player_one, player_two],
+                            rules,
+                            history,
+                        );
+                        online_client.game = Some(game);
+                        commands.trigger(OnlineGameStarted { game, seat: None });
+                    }
+                    ServerMessage::OpponentDisconnected => {
+                        bevy::log::info!("The opponent lost the connection");
+                    }
+                    ServerMessage::OpponentRejoined => {
+                        bevy::log::info!("The opponent rejoined");
+                    }
+                    ServerMessage::OpponentCommitted { round } => {
+                        bevy::log::debug!(
+                            "The opponent has committed to a throw for round {round}"
+                        );
+                    }
+                    ServerMessage::RevealRequested { round } => {
+                        let Some((_, game, remote, _)) = game else {
+                            continue;
+                        };
+                        // spectators are never asked to reveal
+                        let Some(seat) = remote.seat else {
+                            continue;
+                        };
+                        let Ok((_, Some(pending))) = players.get(game.player(seat)) else {
+                            continue;
+                        };
+                        if pending.round == round {
+                            send(
+                                &mut connection,
+                                &ClientMessage::Reveal {
+                                    round,
+                                    guess: pending.guess,
+                                    nonce: pending.nonce,
+                                },
+                            );
+                        }
+                    }
+                    ServerMessage::RoundResolved { record } => {
+                        let Some((game_entity, game, _, mut history)) = game else {
+                            continue;
+                        };
+                        for seat in [Seat::PlayerOne, Seat::PlayerTwo] {
+                            let player = game.player(seat);
+                            let side = record.side(seat);
+                            if let Ok((mut health, _)) = players.get_mut(player) {
+                                **health = side.health;
+                            }
+                            mirror_side(&mut commands, player, side);
+                        }
+                        history.push(record.clone());
+                        commands
+                            .entity(game_entity)
+                            .insert(LastOutcome(record.to_outcome()));
+                        commands.trigger(RoundResolved {
+                            game: game_entity,
+                            round: record,
+                        });
+                    }
+                    ServerMessage::GameEnded { winner } => {
+                        // a finished game can no longer be rejoined
+                        online_client.session = None;
+                        let Some((game_entity, game, ..)) = game else {
+                            continue;
+                        };
+                        let result = match winner {
+                            Some(seat) => GameOver::Winner(game.player(seat)),
+                            None => GameOver::Draw,
+                        };
+                        commands.entity(game_entity).insert(result);
+                        commands.trigger(GameEnded {
+                            game: game_entity,
+                            result,
+                        });
+                    }
+                    ServerMessage::Rejected { error } => {
+                        bevy::log::warn!("The server rejected a message: {error}");
+                        match game {
+                            // a rejected reveal is discarded, so the player can throw again
+                            Some((_, game, remote, _)) => {
+                                if let Some(seat) = remote.seat {
+                                    commands.entity(game.player(seat)).remove::<PendingReveal>();
+                                }
+                            }
+                            None if online_client.game.is_none() => {
+                                commands.trigger(OnlineJoinRejected { client, error });
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn despawn_finished_games(
+        trigger: Trigger<GameEnded>,
+        mut commands: Commands,
+        games: Query<&Game, With<RemoteGame>>,
+    ) {
+        let game_entity = trigger.event().game;
+        let Ok(game) = games.get(game_entity) else {
+            return;
+        };
+        despawn_game(&mut commands, game_entity, game);
+    }
+
+    /// Commits the local player's throw to the server instead of resolving it locally.
+    fn commit_guesses(
+        mut commands: Commands,
+        players: Query<(Entity, &Guess, &InGame, Option<&PendingReveal>)>,
+        games: Query<(&Game, &RemoteGame, &RoundHistory), Without<GameOver>>,
+        mut connections: Query<&mut Connection, With<OnlineClient>>,
+    ) {
+        for (player, guess, in_game, pending) in &players {
+            let Ok((game, remote, history)) = games.get(**in_game) else {
+                continue;
+            };
+            if game.seat(player) != remote.seat {
+                continue;
+            }
+            commands.entity(player).remove::<Guess>();
+            // a commitment cannot be changed
+            if pending.is_some() {
+                continue;
+            }
+            let Ok(mut connection) = connections.get_mut(remote.connection) else {
+                continue;
+            };
+            let pending = PendingReveal {
+                round: history.len() as u32,
+                guess: **guess,
+                // a predictable nonce would let the opponent work out the throw from its commitment
+                nonce: OsRng.next_u64(),
+            };
+            send(
+                &mut connection,
+                &ClientMessage::Commit {
+                    round: pending.round,
+                    commitment: Commitment::new(pending.round, pending.guess, pending.nonce),
+                },
+            );
+            commands.entity(player).insert(pending);
+        }
+    }
+}
+
+/// Spawns a local copy of a game hosted by the server, caught up with the rounds in `history`.
+fn spawn_game(
+    commands: &mut Commands,
+    client: Entity,
+    seat: Option<Seat>,
+    players: [PlayerElements; 2],
+    rules: GameRules,
+    history: RoundHistory,
+) -> Entity {
+    let last_round = history.last().cloned();
+    let [player_one, player_two] = players;
+    let [player_one, player_two] = [(Seat::PlayerOne, player_one), (Seat::PlayerTwo, player_two)]
+        .map(|(seat, elements)| commands.spawn((Name::new(seat.to_string()), elements)).id());
+    let game = commands
+        .spawn((
+            RemoteGame {
+                connection: client,
+                seat,
+            },
+            history,
+        ))
+        .id();
+    commands.trigger(SpawnGame {
+        game: Some(game),
+        player_one: Some(player_one),
+        player_two: Some(player_two),
+        rules: Some(GameRules {
+            starting_health: match &last_round {
+                Some(record) => (
+                    Some(record.player_one.health),
+                    Some(record.player_two.health),
+                ),
+                None => rules.starting_health,
+            },
+            throw_timer: None,
+            ..rules
+        }),
+        ..Default::default()
+    });
+    if let Some(record) = last_round {
+        mirror_side(commands, player_one, &record.player_one);
+        mirror_side(commands, player_two, &record.player_two);
+    }
+    game
+}
+
+/// Copies the state a player carries into the next round from a round resolved by the server.
+fn mirror_side(commands: &mut Commands, player: Entity, side: &RoundSide) {
+    let mut player = commands.entity(player);
+    player.remove::<(PendingReveal, Combo, Stagger, ForcedAction)>();
+    if side.combo {
+        player.insert(Combo);
+    }
+    if side.staggered {
+        player.insert((Stagger, ForcedAction(side.action.guess)));
+    }
+}
+
+fn despawn_game(commands: &mut Commands, game_entity: Entity, game: &Game) {
+    commands.entity(game.player_one).despawn();
+    commands.entity(game.player_two).despawn();
+    commands.entity(game_entity).despawn();
+}
+
+fn send(connection: &mut Connection, message: &ClientMessage) {
+    if let Err(error) = connection.send(message) {
+        bevy::log::warn!("Failed to send {message:?}: {error}");
+    }
+}
+
+/// Where the home menu connects to play online.
+#[derive(Clone, Debug)]
+#[derive(Resource)]
+pub struct OnlineSettings {
+    pub server_url: String,
+}
+
+impl Default for OnlineSettings {
+    fn default() -> Self {
+        Self {
+            server_url: "ws://127.0.0.1:7878".to_string(),
+        }
+    }
+}
+
+/// A connection to a server, which joins a lobby or starts watching a game as soon as it is spawned.
+/// Spawn a new client, with a new [`Connection`], to play again.
+/// If a player loses the connection mid-game, insert a new [`Connection`] to rejoin the game.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+#[require(Name(|| Name::new("Online Client")))]
+pub struct OnlineClient {
+    pub role: OnlineRole,
+    /// The game being played or watched, once the server has sent it.
+    pub game: Option<Entity>,
+    session: Option<u64>,
+}
+
+impl OnlineClient {
+    pub fn new(elements: PlayerElements, lobby: Lobby) -> Self {
+        Self::with_role(OnlineRole::Player { elements, lobby })
+    }
+
+    pub fn spectator(code: Option<RoomCode>) -> Self {
+        Self::with_role(OnlineRole::Spectator { code })
+    }
+
+    fn with_role(role: OnlineRole) -> Self {
+        Self {
+            role,
+            game: None,
+            session: None,
+        }
+    }
+
+    /// Whether the client lost the connection to a game it could still rejoin.
+    pub fn can_rejoin(&self) -> bool {
+        self.game.is_some() && self.session.is_some()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum OnlineRole {
+    Player {
+        elements: PlayerElements,
+        lobby: Lobby,
+    },
+    /// Watches the game from room `code`, or any game from a quick match, without playing.
+    Spectator { code: Option<RoomCode> },
+}
+
+/// A local copy of a game resolved by the server.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct RemoteGame {
+    /// The entity holding the [`Connection`] to the server.
+    pub connection: Entity,
+    /// The local player's seat, or `None` when only watching the game.
+    pub seat: Option<Seat>,
+}
+
+/// A throw committed to the server, held until both players have committed.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct PendingReveal {
+    pub round: u32,
+    pub guess: Element,
+    nonce: u64,
+}
+
+/// The server opened a private room, which an opponent can join with `code`.
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct OnlineRoomCreated {
+    pub client: Entity,
+    pub code: RoomCode,
+}
+
+/// The server refused to pair the client, such as when the room it asked for does not exist.
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct OnlineJoinRejected {
+    pub client: Entity,
+    pub error: ProtocolError,
+}
+
+/// The client could not open a connection to the server.
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct OnlineConnectionFailed {
+    pub client: Entity,
+    pub error: TransportError,
+}
+
+/// The server has paired the client with an opponent, or the client has rejoined or started
+/// watching a game.
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct OnlineGameStarted {
+    pub game: Entity,
+    /// `None` for spectators.
+    pub seat: Option<Seat>,
+}
@@ -0,0 +1,11 @@
+mod client;
+pub use client::*;
+
+mod protocol;
+pub use protocol::*;
+
+mod server;
+pub use server::*;
+
+mod transport;
+pub use transport::*;
@@ -0,0 +1,191 @@
+use derive_more::derive::{Display, Error};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Element, GameRules, PlayerElements, RoundHistory, RoundRecord, Seat};
+
+/// A hash binding a player to a throw without revealing it.
+/// The throw is only accepted once it is revealed with the nonce used to make the commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize)]
+pub struct Commitment([u8; 32]);
+
+impl Commitment {
+    pub fn new(round: u32, guess: Element, nonce: u64) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(round.to_le_bytes());
+        hasher.update(guess.key().as_bytes());
+        hasher.update(nonce.to_le_bytes());
+        Self(hasher.finalize().into())
+    }
+
+    pub fn verify(&self, round: u32, guess: Element, nonce: u64) -> bool {
+        *self == Self::new(round, guess, nonce)
+    }
+}
+
+/// A short code naming a private room, shared between friends so they can play each other.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Display)]
+#[derive(Deserialize, Serialize)]
+pub struct RoomCode(String);
+
+impl RoomCode {
+    pub const LENGTH: usize = 4;
+    /// Letters which are hard to mix up when read aloud or typed.
+    const LETTERS: &'static [u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self(
+            (0..Self::LENGTH)
+                .map(|_| Self::LETTERS[rng.gen_range(0..Self::LETTERS.len())] as char)
+                .collect(),
+        )
+    }
+
+    /// Reads a code typed by a player, ignoring case. `None` if it could never name a room.
+    pub fn parse(text: &str) -> Option<Self> {
+        let code = text.trim().to_ascii_uppercase();
+        let is_valid = code.len() == Self::LENGTH
+            && code.bytes().all(|letter| Self::LETTERS.contains(&letter));
+        is_valid.then_some(Self(code))
+    }
+}
+
+/// How a joining player wants to find an opponent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Deserialize, Serialize)]
+pub enum Lobby {
+    /// Plays whoever else is waiting for a quick match.
+    #[default]
+    QuickMatch,
+    /// Opens a private room, and waits for someone to join it by its code.
+    CreateRoom,
+    JoinRoom(RoomCode),
+}
+
+/// Sent from a client to the server.
+/// Rounds are numbered from 0, and each number is the count of rounds resolved before it.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum ClientMessage {
+    /// Asks to be paired with an opponent from `lobby`.
+    Join {
+        elements: PlayerElements,
+        lobby: Lobby,
+    },
+    /// Takes back a seat after losing the connection, using the session sent when the game started.
+    Rejoin {
+        session: u64,
+    },
+    /// Watches the game started from room `code`, or any game started from a quick match.
+    Spectate {
+        code: Option<RoomCode>,
+    },
+    Commit {
+        round: u32,
+        commitment: Commitment,
+    },
+    Reveal {
+        round: u32,
+        guess: Element,
+        nonce: u64,
+    },
+}
+
+/// Sent from the server to a client.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub enum ServerMessage {
+    /// A private room was opened, and is waiting for a second player.
+    RoomCreated {
+        code: RoomCode,
+    },
+    /// Sent when a game starts, and again when a player rejoins it.
+    /// `session` is private to the player, and lets them rejoin if they lose the connection.
+    Started {
+        seat: Seat,
+        session: u64,
+        player_one: PlayerElements,
+        player_two: PlayerElements,
+        rules: GameRules,
+        /// Every round resolved so far, which is empty unless the player rejoined.
+        history: RoundHistory,
+    },
+    /// Sent to a spectator once, with the game as it stands. Rounds follow as they are resolved.
+    Spectating {
+        player_one: PlayerElements,
+        player_two: PlayerElements,
+        rules: GameRules,
+        history: RoundHistory,
+    },
+    /// The opponent lost the connection, and forfeits unless they rejoin in time.
+    OpponentDisconnected,
+    OpponentRejoined,
+    /// The opponent has committed to a throw for `round`.
+    OpponentCommitted {
+        round: u32,
+    },
+    /// Both players have committed to a throw, so each should reveal theirs.
+    RevealRequested {
+        round: u32,
+    },
+    RoundResolved {
+        record: RoundRecord,
+    },
+    /// `None` when the game was drawn.
+    GameEnded {
+        winner: Option<Seat>,
+    },
+    /// The last message from this client was ignored.
+    Rejected {
+        error: ProtocolError,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Display, Error)]
+#[derive(Deserialize, Serialize)]
+pub enum ProtocolError {
+    #[display("The message could not be read")]
+    Malformed,
+    #[display("The player has already joined a game")]
+    AlreadyJoined,
+    #[display(
+        "The player must have at least {} elements",
+        PlayerElements::MIN_ELEMENTS
+    )]
+    TooFewElements,
+    #[display("There is no room {_0}")]
+    RoomNotFound(#[error(not(source))] RoomCode),
+    #[display("Room {_0} already has two players")]
+    RoomFull(#[error(not(source))] RoomCode),
+    #[display("There is no game to watch")]
+    GameNotFound,
+    #[display("Spectators cannot play")]
+    Spectator,
+    #[display("The game could not be rejoined")]
+    SessionNotFound,
+    #[display("The player is not in a game")]
+    NotInGame,
+    #[display("The game is not on round {_0}")]
+    WrongRound(#[error(not(source))] u32),
+    #[display("The player has already committed to a throw this round")]
+    AlreadyCommitted,
+    #[display("The player has not committed to a throw this round")]
+    NotCommitted,
+    #[display("The revealed throw does not match the commitment")]
+    CommitmentMismatch,
+    #[display("The player cannot throw {_0}")]
+    IllegalThrow(#[error(not(source))] Element),
+}
+
+/// Messages travel as RON text, so any transport able to carry strings can carry the protocol.
+pub fn encode<T: Serialize>(message: &T) -> String {
+    ron::to_string(message).expect("protocol messages should always serialize")
+}
+
+pub fn decode<'a, T: Deserialize<'a>>(text: &'a str) -> Result<T, ProtocolError> {
+    ron::from_str(text).map_err(|_| ProtocolError::Malformed)
+}
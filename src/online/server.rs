@@ -0,0 +1,738 @@
+use std::{collections::HashMap, time::Duration};
+
+use rand::{rngs::OsRng, RngCore};
+
+use bevy::prelude::{
+    App, Commands, Component, Entity, IntoSystemConfigs, Name, Plugin, Query, Res, ResMut,
+    Resource, Time, Timer, TimerMode, Trigger, Update, With, Without, World,
+};
+
+use crate::{
+    Combo, Element, ForcedAction, Game, GameEnded, GameOver, GameRules, GameSystems, Guess, Health,
+    InGame, PlayerElements, RoundHistory, RoundResolved, Seat, SpawnGame, Stagger,
+};
+
+use super::{
+    decode, ClientMessage, Commitment, Connection, Lobby, ProtocolError, RoomCode, ServerMessage,
+};
+
+type ConnectedPlayers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Connection,
+        &'static mut RemotePlayer,
+        Option<&'static InGame>,
+        Option<&'static PlayerElements>,
+        Option<&'static ForcedAction>,
+    ),
+>;
+
+type HostedGames<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Game,
+        &'static RoundHistory,
+        &'static GameRules,
+        &'static HostedGame,
+    ),
+    Without<GameOver>,
+>;
+
+/// Hosts online games. Clients who join are paired up, and each round is resolved by
+/// [`crate::GamePlugin`] once both players have revealed throws matching their commitments.
+pub struct OnlineServerPlugin;
+
+impl Plugin for OnlineServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HostedGameRules>();
+        app.init_resource::<DisconnectGracePeriod>();
+        app.init_resource::<NextTicket>();
+        app.add_observer(Self::send_rounds);
+        app.add_observer(Self::send_results);
+        app.add_systems(
+            Update,
+            (
+                Self::receive_messages,
+                Self::receive_spectator_messages,
+                Self::forfeit_abandoned_games,
+                Self::pair_players,
+            )
+                .chain()
+                .before(GameSystems),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(
+            Update,
+            (Self::accept_connections, Self::finish_handshakes)
+                .chain()
+                .before(Self::receive_messages),
+        );
+    }
+}
+
+impl OnlineServerPlugin {
+    /// Starts the handshake with each client connecting to the [`super::WebSocketListener`].
+    #[cfg(not(target_arch = "wasm32"))]
+    fn accept_connections(mut commands: Commands, listener: Option<Res<super::WebSocketListener>>) {
+        let Some(listener) = listener else {
+            return;
+        };
+        loop {
+            match listener.accept() {
+                Ok(Some(pending)) => {
+                    commands.spawn(pending);
+                }
+                Ok(None) => break,
+                Err(error) => {
+                    bevy::log::warn!("Failed to accept a client: {error}");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Spawns a [`RemotePlayer`] for each client which finished the handshake.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn finish_handshakes(
+        mut commands: Commands,
+        mut pending: Query<(Entity, &mut super::PendingConnection), Without<super::OnlineClient>>,
+    ) {
+        for (entity, mut connection) in &mut pending {
+            match connection.poll() {
+                Some(Ok(connection)) => {
+                    commands
+                        .entity(entity)
+                        .remove::<super::PendingConnection>()
+                        .insert((connection, RemotePlayer::default()));
+                }
+                Some(Err(error)) => {
+                    bevy::log::info!("A client failed the handshake: {error}");
+                    commands.entity(entity).despawn();
+                }
+                None => {}
+            }
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn receive_messages(
+        mut commands: Commands,
+        mut players: ConnectedPlayers,
+        disconnected: Query<
+            (Entity, &RemotePlayer, &InGame),
+            (With<Disconnected>, Without<Connection>),
+        >,
+        player_elements: Query<&PlayerElements>,
+        games: HostedGames,
+        grace_period: Res<DisconnectGracePeriod>,
+        mut next_ticket: ResMut<NextTicket>,
+    ) {
+        let mut messages = Vec::new();
+        let mut lost = Vec::new();
+        for (player, mut connection, _, in_game, ..) in &mut players {
+            loop {
+                match connection.receive() {
+                    Ok(Some(text)) => messages.push((player, decode::<ClientMessage>(&text))),
+                    Ok(None) => break,
+                    Err(error) => {
+                        bevy::log::info!("{player} disconnected: {error}");
+                        lost.push((player, in_game.map(|in_game| **in_game)));
+                        break;
+                    }
+                }
+            }
+        }
+
+        for (player, message) in messages {
+            let result = message.and_then(|message| match message {
+                ClientMessage::Join { elements, lobby } => Self::join(
+                    &mut commands,
+                    &mut players,
+                    &games,
+                    &mut next_ticket,
+                    player,
+                    elements,
+                    lobby,
+                ),
+                ClientMessage::Spectate { code } => Self::spectate(
+                    &mut commands,
+                    &mut players,
+                    &games,
+                    &player_elements,
+                    player,
+                    code,
+                ),
+                ClientMessage::Rejoin { session } => Self::rejoin(
+                    &mut commands,
+                    &mut players,
+                    &disconnected,
+                    &player_elements,
+                    &games,
+                    player,
+                    session,
+                ),
+                message => {
+                    Self::handle_message(&mut commands, &mut players, &games, player, message)
+                }
+            });
+            if let Err(error) = result {
+                if let Ok((_, mut connection, ..)) = players.get_mut(player) {
+                    send(&mut connection, &ServerMessage::Rejected { error });
+                }
+            }
+        }
+
+        for (player, game) in lost {
+            let Some((_, game, ..)) = game.and_then(|game| games.get(game).ok()) else {
+                commands.entity(player).despawn();
+                continue;
+            };
+            // the seat is held for a while, in case the player rejoins
+            commands
+                .entity(player)
+                .remove::<Connection>()
+                .insert(Disconnected(Timer::new(grace_period.0, TimerMode::Once)));
+            let opponent = game.seat(player).map(|seat| game.player(seat.opponent()));
+            if let Some(Ok((_, mut connection, ..))) =
+                opponent.map(|opponent| players.get_mut(opponent))
+            {
+                send(&mut connection, &ServerMessage::OpponentDisconnected);
+            }
+        }
+    }
+
+    fn join(
+        commands: &mut Commands,
+        players: &mut ConnectedPlayers,
+        games: &HostedGames,
+        next_ticket: &mut NextTicket,
+        player: Entity,
+        elements: PlayerElements,
+        lobby: Lobby,
+    ) -> Result<(), ProtocolError> {
+        let Ok((_, _, remote, in_game, ..)) = players.get(player) else {
+            return Err(ProtocolError::NotInGame);
+        };
+        if in_game.is_some() || remote.has_joined() {
+            return Err(ProtocolError::AlreadyJoined);
+        }
+        if elements.elements.len() < PlayerElements::MIN_ELEMENTS {
+            return Err(ProtocolError::TooFewElements);
+        }
+
+        let players_in_room = |code: &RoomCode| {
+            players
+                .iter()
+                .filter(|(_, _, remote, ..)| {
+                    matches!(&remote.queue, Some(Queue::Room(room)) if room == code)
+                })
+                .count()
+        };
+        let is_new_room = lobby == Lobby::CreateRoom;
+        let queue = match lobby {
+            Lobby::QuickMatch => Queue::QuickMatch,
+            Lobby::CreateRoom => {
+                // room codes are drawn from the operating system, so they cannot be guessed
+                let code = std::iter::repeat_with(|| RoomCode::random(&mut OsRng))
+                    .find(|code| {
+                        players_in_room(code) == 0
+                            && !games
+                                .iter()
+                                .any(|(.., hosted)| hosted.room.as_ref() == Some(code))
+                    })
+                    .expect("an unused room code should eventually be found");
+                Queue::Room(code)
+            }
+            Lobby::JoinRoom(code) => match players_in_room(&code) {
+                0 => return Err(ProtocolError::RoomNotFound(code)),
+                1 => Queue::Room(code),
+                _ => return Err(ProtocolError::RoomFull(code)),
+            },
+        };
+
+        let Ok((_, mut connection, mut remote, ..)) = players.get_mut(player) else {
+            return Err(ProtocolError::NotInGame);
+        };
+        if let (true, Queue::Room(code)) = (is_new_room, &queue) {
+            send(
+                &mut connection,
+                &ServerMessage::RoomCreated { code: code.clone() },
+            );
+        }
+        remote.queue = Some(queue);
+        remote.ticket = next_ticket.0;
+        next_ticket.0 += 1;
+        commands.entity(player).insert(elements);
+        Ok(())
+    }
+
+    /// Moves a new connection into the seat of a disconnected player with the same session.
+    #[allow(clippy::type_complexity)]
+    fn rejoin(
+        commands: &mut Commands,
+        players: &mut ConnectedPlayers,
+        disconnected: &Query<
+            (Entity, &RemotePlayer, &InGame),
+            (With<Disconnected>, Without<Connection>),
+        >,
+        elements: &Query<&PlayerElements>,
+        games: &HostedGames,
+        player: Entity,
+        session: u64,
+    ) -> Result<(), ProtocolError> {
+        let Ok((_, _, remote, in_game, ..)) = players.get(player) else {
+            return Err(ProtocolError::NotInGame);
+        };
+        if in_game.is_some() || remote.has_joined() {
+            return Err(ProtocolError::AlreadyJoined);
+        }
+        let (seated, _, in_game) = disconnected
+            .iter()
+            .find(|(_, remote, _)| remote.session == Some(session))
+            .ok_or(ProtocolError::SessionNotFound)?;
+        let (_, game, history, rules, _) = games
+            .get(**in_game)
+            .map_err(|_| ProtocolError::SessionNotFound)?;
+        let seat = game.seat(seated).ok_or(ProtocolError::SessionNotFound)?;
+        let Ok([player_one, player_two]) = elements.get_many([game.player_one, game.player_two])
+        else {
+            return Err(ProtocolError::SessionNotFound);
+        };
+        let started = ServerMessage::Started {
+            seat,
+            session,
+            player_one: player_one.clone(),
+            player_two: player_two.clone(),
+            rules: rules.clone(),
+            history: history.clone(),
+        };
+
+        if let Ok((_, mut connection, ..)) = players.get_mut(game.player(seat.opponent())) {
+            send(&mut connection, &ServerMessage::OpponentRejoined);
+        }
+        commands.queue(move |world: &mut World| {
+            let Some(mut connection) = world.entity_mut(player).take::<Connection>() else {
+                return;
+            };
+            send(&mut connection, &started);
+            world.despawn(player);
+            let mut seated = world.entity_mut(seated);
+            seated.remove::<Disconnected>().insert(connection);
+            // the client lost its commitment along with the connection, so it commits again
+            if let Some(mut remote) = seated.get_mut::<RemotePlayer>() {
+                remote.clear_throw();
+            }
+        });
+        Ok(())
+    }
+
+    /// Starts sending a game to a client which only watches it.
+    fn spectate(
+        commands: &mut Commands,
+        players: &mut ConnectedPlayers,
+        games: &HostedGames,
+        player_elements: &Query<&PlayerElements>,
+        player: Entity,
+        code: Option<RoomCode>,
+    ) -> Result<(), ProtocolError> {
+        let Ok((_, mut connection, remote, in_game, ..)) = players.get_mut(player) else {
+            return Err(ProtocolError::NotInGame);
+        };
+        if in_game.is_some() || remote.has_joined() {
+            return Err(ProtocolError::AlreadyJoined);
+        }
+        // games in private rooms can only be found by their code
+        let (game_entity, game, history, rules, _) = games
+            .iter()
+            .find(|(.., hosted)| hosted.room == code)
+            .ok_or(ProtocolError::GameNotFound)?;
+        let Ok([player_one, player_two]) =
+            player_elements.get_many([game.player_one, game.player_two])
+        else {
+            return Err(ProtocolError::GameNotFound);
+        };
+        send(
+            &mut connection,
+            &ServerMessage::Spectating {
+                player_one: player_one.clone(),
+                player_two: player_two.clone(),
+                rules: rules.clone(),
+                history: history.clone(),
+            },
+        );
+        commands
+            .entity(player)
+            .remove::<RemotePlayer>()
+            .insert(Spectator { game: game_entity });
+        Ok(())
+    }
+
+    fn handle_message(
+        commands: &mut Commands,
+        players: &mut ConnectedPlayers,
+        games: &HostedGames,
+        player: Entity,
+        message: ClientMessage,
+    ) -> Result<(), ProtocolError> {
+        let Ok((_, _, mut remote, in_game, player_elements, forced_action)) =
+            players.get_mut(player)
+        else {
+            return Err(ProtocolError::NotInGame);
+        };
+        let (_, game, history, ..) = in_game
+            .and_then(|in_game| games.get(**in_game).ok())
+            .ok_or(ProtocolError::NotInGame)?;
+        let current_round = history.len() as u32;
+        let seat = game.seat(player).ok_or(ProtocolError::NotInGame)?;
+        let opponent = game.player(seat.opponent());
+
+        match message {
+            ClientMessage::Join { .. }
+            | ClientMessage::Rejoin { .. }
+            | ClientMessage::Spectate { .. } => {
+                unreachable!("joins are handled by the caller")
+            }
+            ClientMessage::Commit { round, commitment } => {
+                if round != current_round {
+                    return Err(ProtocolError::WrongRound(current_round));
+                }
+                if remote.commitment.is_some() {
+                    return Err(ProtocolError::AlreadyCommitted);
+                }
+                remote.commitment = Some(commitment);
+
+                let Ok((_, mut connection, opponent_remote, ..)) = players.get_mut(opponent) else {
+                    return Ok(());
+                };
+                send(&mut connection, &ServerMessage::OpponentCommitted { round });
+                if opponent_remote.commitment.is_some() {
+                    for player in [player, opponent] {
+                        if let Ok((_, mut connection, ..)) = players.get_mut(player) {
+                            send(&mut connection, &ServerMessage::RevealRequested { round });
+                        }
+                    }
+                }
+            }
+            ClientMessage::Reveal {
+                round,
+                guess,
+                nonce,
+            } => {
+                if round != current_round {
+                    return Err(ProtocolError::WrongRound(current_round));
+                }
+                let commitment = remote.commitment.ok_or(ProtocolError::NotCommitted)?;
+                if !commitment.verify(round, guess, nonce) {
+                    return Err(ProtocolError::CommitmentMismatch);
+                }
+                let is_legal = player_elements
+                    .is_some_and(|elements| elements.elements.contains(&guess))
+                    && forced_action.is_none_or(|ForcedAction(forced)| *forced == guess);
+                if !is_legal {
+                    // nothing has been revealed to the opponent, so the player may commit again
+                    remote.commitment = None;
+                    return Err(ProtocolError::IllegalThrow(guess));
+                }
+                remote.revealed = Some(guess);
+
+                let Ok([(.., remote, _, _, _), (.., opponent_remote, _, _, _)]) =
+                    players.get_many_mut([player, opponent])
+                else {
+                    return Ok(());
+                };
+                let (Some(guess), Some(opponent_guess)) =
+                    (remote.revealed, opponent_remote.revealed)
+                else {
+                    return Ok(());
+                };
+                for (mut remote, player, guess) in [
+                    (remote, player, guess),
+                    (opponent_remote, opponent, opponent_guess),
+                ] {
+                    remote.clear_throw();
+                    commands.entity(player).insert(Guess::new(guess));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Spectators only ever listen, so anything they send is rejected.
+    fn receive_spectator_messages(
+        mut commands: Commands,
+        mut spectators: Query<(Entity, &mut Connection), With<Spectator>>,
+    ) {
+        for (spectator, mut connection) in &mut spectators {
+            loop {
+                match connection.receive() {
+                    Ok(Some(_)) => send(
+                        &mut connection,
+                        &ServerMessage::Rejected {
+                            error: ProtocolError::Spectator,
+                        },
+                    ),
+                    Ok(None) => break,
+                    Err(error) => {
+                        bevy::log::info!("Spectator {spectator} disconnected: {error}");
+                        commands.entity(spectator).despawn();
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// A player who stays disconnected for the whole grace period loses the game.
+    fn forfeit_abandoned_games(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut players: Query<(Entity, &mut Disconnected)>,
+    ) {
+        for (player, mut disconnected) in &mut players {
+            if disconnected.0.tick(time.delta()).finished() {
+                commands
+                    .entity(player)
+                    .remove::<Disconnected>()
+                    .insert(Health::new(0));
+            }
+        }
+    }
+
+    /// Pairs quick matches in the order players joined, and rooms once their second player joins.
+    fn pair_players(
+        mut commands: Commands,
+        mut players: Query<(Entity, &mut Connection, &mut RemotePlayer, &PlayerElements)>,
+        rules: Res<HostedGameRules>,
+    ) {
+        let mut waiting: Vec<_> = players
+            .iter()
+            .filter_map(|(player, _, remote, _)| {
+                Some((remote.ticket, player, remote.queue.as_ref()?))
+            })
+            .collect();
+        waiting.sort_by_key(|(ticket, ..)| *ticket);
+
+        let mut open = HashMap::new();
+        let mut pairs = Vec::new();
+        for (_, player, queue) in waiting {
+            match open.remove(queue) {
+                Some(waiting) => pairs.push((queue.clone(), [waiting, player])),
+                None => {
+                    open.insert(queue.clone(), player);
+                }
+            }
+        }
+
+        // commitments have no deadline, so throw timers are not used online
+        let rules = GameRules {
+            throw_timer: None,
+            ..rules.0.clone()
+        };
+        for (queue, pair) in pairs {
+            let Ok([one, two]) = players.get_many_mut(pair) else {
+                continue;
+            };
+            let (player_one, player_two) = (one.3.clone(), two.3.clone());
+            let room = match queue {
+                Queue::QuickMatch => None,
+                Queue::Room(code) => Some(code),
+            };
+            let game = commands.spawn(HostedGame { room }).id();
+            commands.trigger(SpawnGame {
+                game: Some(game),
+                player_one: Some(one.0),
+                player_two: Some(two.0),
+                rules: Some(rules.clone()),
+                ..Default::default()
+            });
+            for ((_, mut connection, mut remote, _), seat) in
+                [(one, Seat::PlayerOne), (two, Seat::PlayerTwo)]
+            {
+                // anyone holding the session can take over the seat, so it must not be predictable
+                let session = OsRng.next_u64();
+                remote.queue = None;
+                remote.session = Some(session);
+                send(
+                    &mut connection,
+                    &ServerMessage::Started {
+                        seat,
+                        session,
+                        player_one: player_one.clone(),
+                        player_two: player_two.clone(),
+                        rules: rules.clone(),
+                        history: RoundHistory::default(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Sends each round to the players and spectators once both throws have been revealed.
+    fn send_rounds(
+        trigger: Trigger<RoundResolved>,
+        games: Query<&Game, With<HostedGame>>,
+        mut connections: Query<&mut Connection, Without<Spectator>>,
+        mut spectators: Query<(&mut Connection, &Spectator)>,
+    ) {
+        let RoundResolved {
+            game: game_entity,
+            round,
+        } = trigger.event();
+        let Ok(game) = games.get(*game_entity) else {
+            return;
+        };
+        let message = ServerMessage::RoundResolved {
+            record: round.clone(),
+        };
+        for player in [game.player_one, game.player_two] {
+            if let Ok(mut connection) = connections.get_mut(player) {
+                send(&mut connection, &message);
+            }
+        }
+        for (mut connection, spectator) in &mut spectators {
+            if spectator.game == *game_entity {
+                send(&mut connection, &message);
+            }
+        }
+    }
+
+    /// Tells both players and any spectators the result, and frees them to join another game.
+    fn send_results(
+        trigger: Trigger<GameEnded>,
+        mut commands: Commands,
+        games: Query<&Game, With<HostedGame>>,
+        mut connections: Query<(&mut Connection, &mut RemotePlayer)>,
+        mut spectators: Query<(Entity, &mut Connection, &Spectator), Without<RemotePlayer>>,
+    ) {
+        let GameEnded {
+            game: game_entity,
+            result,
+        } = *trigger.event();
+        let Ok(game) = games.get(game_entity) else {
+            return;
+        };
+        let winner = match result {
+            GameOver::Winner(winner) => game.seat(winner),
+            GameOver::Draw => None,
+        };
+        for player in [game.player_one, game.player_two] {
+            if let Ok((mut connection, mut remote)) = connections.get_mut(player) {
+                send(&mut connection, &ServerMessage::GameEnded { winner });
+                *remote = RemotePlayer::default();
+                commands
+                    .entity(player)
+                    .remove::<(InGame, Guess, Combo, Stagger, ForcedAction)>();
+            } else {
+                // the player disconnected during the game
+                commands.entity(player).despawn();
+            }
+        }
+        for (spectator, mut connection, Spectator { game }) in &mut spectators {
+            if *game == game_entity {
+                send(&mut connection, &ServerMessage::GameEnded { winner });
+                commands
+                    .entity(spectator)
+                    .remove::<Spectator>()
+                    .insert(RemotePlayer::default());
+            }
+        }
+        commands.entity(game_entity).despawn();
+    }
+}
+
+fn send(connection: &mut Connection, message: &ServerMessage) {
+    if let Err(error) = connection.send(message) {
+        bevy::log::warn!("Failed to send {message:?}: {error}");
+    }
+}
+
+/// The rules of every game the server hosts.
+#[derive(Clone, Debug)]
+#[derive(Resource)]
+pub struct HostedGameRules(pub GameRules);
+
+impl Default for HostedGameRules {
+    fn default() -> Self {
+        Self(GameRules {
+            starting_health: (Some(*Health::PLAYER_MAX), Some(*Health::PLAYER_MAX)),
+            ..Default::default()
+        })
+    }
+}
+
+/// How long a player who loses the connection mid-game has to rejoin before forfeiting.
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource)]
+pub struct DisconnectGracePeriod(pub Duration);
+
+impl Default for DisconnectGracePeriod {
+    fn default() -> Self {
+        Self(Duration::from_secs(30))
+    }
+}
+
+/// A client connected to the server. Once paired, the same entity plays as the client's player.
+#[derive(Clone, Debug, Default)]
+#[derive(Component)]
+#[require(Name(|| Name::new("Remote Player")))]
+pub struct RemotePlayer {
+    queue: Option<Queue>,
+    /// Taken from [`NextTicket`] on joining a queue, to pair players in the order they joined.
+    ticket: u64,
+    /// Proves a rejoining client owns this seat. Set once the player is paired.
+    session: Option<u64>,
+    /// This round's commitment, cleared once the round is resolved.
+    commitment: Option<Commitment>,
+    revealed: Option<Element>,
+}
+
+impl RemotePlayer {
+    /// Whether the player is waiting for a game or seated in one.
+    fn has_joined(&self) -> bool {
+        self.queue.is_some() || self.session.is_some()
+    }
+
+    fn clear_throw(&mut self) {
+        self.commitment = None;
+        self.revealed = None;
+    }
+}
+
+/// The ticket handed to the next player to join a queue.
+#[derive(Debug, Default)]
+#[derive(Resource)]
+struct NextTicket(u64);
+
+/// Where a player waits to be paired.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Queue {
+    QuickMatch,
+    Room(RoomCode),
+}
+
+/// A remote player whose connection was lost mid-game, holding their seat until the timer ends.
+#[derive(Debug)]
+#[derive(Component)]
+pub struct Disconnected(Timer);
+
+/// A client watching a game without playing in it.
+/// Spectators are sent rounds once they are resolved, so they never learn a throw before the opponent.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+#[require(Name(|| Name::new("Spectator")))]
+pub struct Spectator {
+    pub game: Entity,
+}
+
+/// A game hosted for two remote players.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct HostedGame {
+    /// The private room the game was started from, if any.
+    /// Spectators can only find games from rooms by the room's code.
+    pub room: Option<RoomCode>,
+}
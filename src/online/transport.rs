@@ -0,0 +1,265 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use derive_more::derive::{Display, Error};
+use serde::Serialize;
+
+use bevy::prelude::Component;
+
+use super::encode;
+
+/// Carries encoded protocol messages between a client and the server.
+/// Implementations must never block: `receive` returns `Ok(None)` when nothing has arrived yet.
+pub trait Transport: Send + Sync + 'static {
+    fn send(&mut self, text: String) -> Result<(), TransportError>;
+    fn receive(&mut self) -> Result<Option<String>, TransportError>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Display, Error)]
+pub enum TransportError {
+    #[display("The connection was closed")]
+    Closed,
+    #[display("The connection failed: {_0}")]
+    Failed(#[error(not(source))] String),
+}
+
+/// A connection to the other side of the protocol, over any transport.
+#[derive(Component)]
+pub struct Connection(Box<dyn Transport>);
+
+impl Connection {
+    pub fn new(transport: impl Transport) -> Self {
+        Self(Box::new(transport))
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), TransportError> {
+        self.0.send(encode(message))
+    }
+
+    pub fn receive(&mut self) -> Result<Option<String>, TransportError> {
+        self.0.receive()
+    }
+}
+
+/// One end of an in-process connection, for running a client and server in the same process.
+pub struct LocalTransport {
+    outgoing: Arc<Mutex<VecDeque<String>>>,
+    incoming: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl LocalTransport {
+    pub fn pair() -> (Self, Self) {
+        let (a, b) = (Arc::default(), Arc::default());
+        (
+            Self {
+                outgoing: Arc::clone(&a),
+                incoming: Arc::clone(&b),
+            },
+            Self {
+                outgoing: b,
+                incoming: a,
+            },
+        )
+    }
+}
+
+impl Transport for LocalTransport {
+    fn send(&mut self, text: String) -> Result<(), TransportError> {
+        // the other end holds the only other reference to each queue
+        if Arc::strong_count(&self.outgoing) == 1 {
+            return Err(TransportError::Closed);
+        }
+        self.outgoing.lock().unwrap().push_back(text);
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Option<String>, TransportError> {
+        match self.incoming.lock().unwrap().pop_front() {
+            Some(text) => Ok(Some(text)),
+            None if Arc::strong_count(&self.incoming) == 1 => Err(TransportError::Closed),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A connection being opened on another thread, so that slow handshakes never stall a frame.
+/// Poll it each frame until the connection is ready.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+pub struct PendingConnection(Mutex<std::sync::mpsc::Receiver<Result<Connection, TransportError>>>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PendingConnection {
+    /// Runs `connect`, which may block, on a new thread.
+    pub fn new(
+        connect: impl FnOnce() -> Result<Connection, TransportError> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            // the receiver is gone if the connection was abandoned
+            let _ = sender.send(connect());
+        });
+        Self(Mutex::new(receiver))
+    }
+
+    /// The opened connection, or why it failed, once the other thread is done.
+    pub fn poll(&mut self) -> Option<Result<Connection, TransportError>> {
+        match self.0.get_mut().unwrap().try_recv() {
+            Ok(result) => Some(result),
+            Err(std::sync::mpsc::TryRecvError::Empty) => None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Some(Err(TransportError::Closed)),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use websocket::{WebSocketListener, WebSocketTransport};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod websocket {
+    use std::{
+        io::ErrorKind,
+        net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+        time::Duration,
+    };
+
+    use tungstenite::{
+        client::IntoClientRequest, stream::MaybeTlsStream, Error, Message, WebSocket,
+    };
+
+    use bevy::prelude::Resource;
+
+    use super::{Connection, PendingConnection, Transport, TransportError};
+
+    /// Accepts clients connecting over WebSocket.
+    /// Insert it as a resource to have [`crate::OnlineServerPlugin`] host games for them.
+    #[derive(Debug)]
+    #[derive(Resource)]
+    pub struct WebSocketListener(TcpListener);
+
+    impl WebSocketListener {
+        /// How long a client has to finish the handshake before it is dropped.
+        const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+        pub fn bind(address: impl ToSocketAddrs) -> Result<Self, TransportError> {
+            let listener = TcpListener::bind(address).map_err(WebSocketTransport::io_error)?;
+            listener
+                .set_nonblocking(true)
+                .map_err(WebSocketTransport::io_error)?;
+            Ok(Self(listener))
+        }
+
+        /// The address clients connect to, useful after binding to port 0.
+        pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+            self.0.local_addr().map_err(WebSocketTransport::io_error)
+        }
+
+        /// The next client waiting to connect, if any, whose handshake is completed on another thread.
+        pub fn accept(&self) -> Result<Option<PendingConnection>, TransportError> {
+            match self.0.accept() {
+                Ok((stream, _)) => {
+                    stream
+                        .set_read_timeout(Some(Self::HANDSHAKE_TIMEOUT))
+                        .map_err(WebSocketTransport::io_error)?;
+                    Ok(Some(PendingConnection::new(move || {
+                        WebSocketTransport::accept(stream).map(Connection::new)
+                    })))
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(None),
+                Err(error) => Err(WebSocketTransport::io_error(error)),
+            }
+        }
+    }
+
+    /// A WebSocket connection over plain TCP.
+    pub struct WebSocketTransport(WebSocket<MaybeTlsStream<TcpStream>>);
+
+    impl WebSocketTransport {
+        /// How long reaching the server and completing the handshake may each take.
+        const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+        /// Connects to a server at a `ws://` url, blocking until the handshake completes.
+        /// Use a [`super::PendingConnection`] to connect without blocking.
+        pub fn connect(url: &str) -> Result<Self, TransportError> {
+            let request = url.into_client_request().map_err(Self::error)?;
+            let host = request.uri().host().unwrap_or_default();
+            let port = request.uri().port_u16().unwrap_or(80);
+            let mut last_error = TransportError::Failed(format!("Could not find {host}"));
+            for address in (host, port).to_socket_addrs().map_err(Self::io_error)? {
+                let stream = match TcpStream::connect_timeout(&address, Self::CONNECT_TIMEOUT) {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        last_error = Self::io_error(error);
+                        continue;
+                    }
+                };
+                stream
+                    .set_read_timeout(Some(Self::CONNECT_TIMEOUT))
+                    .map_err(Self::io_error)?;
+                let (socket, _) = tungstenite::client(request, MaybeTlsStream::Plain(stream))
+                    .map_err(|error| TransportError::Failed(error.to_string()))?;
+                return Self::new(socket);
+            }
+            Err(last_error)
+        }
+
+        /// Completes the handshake with a client accepted by a `TcpListener`.
+        pub fn accept(stream: TcpStream) -> Result<Self, TransportError> {
+            stream.set_nonblocking(false).map_err(Self::io_error)?;
+            let socket = tungstenite::accept(MaybeTlsStream::Plain(stream))
+                .map_err(|error| TransportError::Failed(error.to_string()))?;
+            Self::new(socket)
+        }
+
+        fn new(socket: WebSocket<MaybeTlsStream<TcpStream>>) -> Result<Self, TransportError> {
+            if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+                stream.set_nonblocking(true).map_err(Self::io_error)?;
+            }
+            Ok(Self(socket))
+        }
+
+        fn error(error: Error) -> TransportError {
+            match error {
+                Error::ConnectionClosed | Error::AlreadyClosed => TransportError::Closed,
+                error => TransportError::Failed(error.to_string()),
+            }
+        }
+
+        fn io_error(error: std::io::Error) -> TransportError {
+            TransportError::Failed(error.to_string())
+        }
+
+        fn is_would_block(error: &Error) -> bool {
+            matches!(error, Error::Io(error) if error.kind() == ErrorKind::WouldBlock)
+        }
+    }
+
+    impl Transport for WebSocketTransport {
+        fn send(&mut self, text: String) -> Result<(), TransportError> {
+            match self.0.send(Message::text(text)) {
+                // the message is queued and written by a later flush
+                Err(error) if Self::is_would_block(&error) => Ok(()),
+                result => result.map_err(Self::error),
+            }
+        }
+
+        fn receive(&mut self) -> Result<Option<String>, TransportError> {
+            match self.0.flush() {
+                Err(error) if !Self::is_would_block(&error) => return Err(Self::error(error)),
+                _ => {}
+            }
+            loop {
+                match self.0.read() {
+                    Ok(Message::Text(text)) => return Ok(Some(text)),
+                    Ok(Message::Close(_)) => return Err(TransportError::Closed),
+                    Ok(_) => {}
+                    Err(error) if Self::is_would_block(&error) => return Ok(None),
+                    Err(error) => return Err(Self::error(error)),
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use derive_more::derive::{Constructor, Deref, DerefMut};
+use derive_more::derive::{Constructor, Deref, DerefMut, Display, Error};
+use serde::{Deserialize, Serialize};
 
 use bevy::prelude::{Component, Name, Reflect};
 
@@ -23,6 +24,22 @@ impl Health {
     pub fn new(hp: u32) -> Self {
         Health(hp)
     }
+
+    /// Heals up to `max_health`, without reducing health that is already above it.
+    pub fn heal(&mut self, amount: u32, max_health: &MaxHealth) {
+        self.0 = self.0.max(self.0.saturating_add(amount).min(max_health.0));
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Deref, DerefMut)]
+#[derive(Component)]
+pub struct MaxHealth(pub u32);
+
+impl From<&Health> for MaxHealth {
+    fn from(health: &Health) -> Self {
+        MaxHealth(health.0)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -32,8 +49,34 @@ pub struct ElementUpgrades {
     pub aspect: Option<Aspect>,
 }
 
+impl std::fmt::Display for ElementUpgrades {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.augmentation, self.aspect) {
+            (None, None) => write!(f, "No upgrades"),
+            (Some(augmentation), None) => write!(f, "{augmentation}"),
+            (None, Some(aspect)) => write!(f, "Aspect of {}", *aspect),
+            (Some(augmentation), Some(aspect)) => {
+                write!(f, "{augmentation}, Aspect of {}", *aspect)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Display, Error)]
+pub enum UpgradeError {
+    #[display("The player does not have the element {_0}")]
+    MissingElement(#[error(not(source))] Element),
+    #[display(
+        "The player must keep at least {} elements",
+        PlayerElements::MIN_ELEMENTS
+    )]
+    TooFewElements,
+}
+
 #[derive(Clone, Debug, Default)]
 #[derive(Constructor)]
+#[derive(Deserialize, Serialize)]
 #[derive(Component, Reflect)]
 pub struct PlayerElements {
     pub elements: BTreeSet<Element>,
@@ -42,6 +85,9 @@ pub struct PlayerElements {
 }
 
 impl PlayerElements {
+    /// Players must always have at least this many elements to choose from.
+    pub const MIN_ELEMENTS: usize = 2;
+
     pub fn from_set(elements: impl IntoIterator<Item = Element>) -> Self {
         Self {
             elements: elements.into_iter().collect(),
@@ -54,14 +100,41 @@ impl PlayerElements {
         self.elements.insert(element);
     }
 
-    pub fn augment(&mut self, element: Element, augmentation: Augmentation) {
-        assert!(self.elements.contains(&element));
-        self.augmentations.insert(element, augmentation);
+    pub fn remove(&mut self, element: Element) {
+        self.elements.remove(&element);
+        self.augmentations.remove(&element);
+        self.enchantments.remove(&element);
+    }
+
+    /// Augments `element`, returning the augmentation it replaced.
+    pub fn augment(
+        &mut self,
+        element: Element,
+        augmentation: Augmentation,
+    ) -> Result<Option<Augmentation>, UpgradeError> {
+        if !self.elements.contains(&element) {
+            return Err(UpgradeError::MissingElement(element));
+        }
+        Ok(self.augmentations.insert(element, augmentation))
+    }
+
+    /// Enchants `element` with an aspect, returning the aspect it replaced.
+    pub fn enchant(
+        &mut self,
+        element: Element,
+        aspect: Aspect,
+    ) -> Result<Option<Aspect>, UpgradeError> {
+        if !self.elements.contains(&element) {
+            return Err(UpgradeError::MissingElement(element));
+        }
+        Ok(self.enchantments.insert(element, aspect))
     }
 
-    pub fn enchant(&mut self, element: Element, aspect: Aspect) {
-        assert!(self.elements.contains(&element));
-        self.enchantments.insert(element, aspect);
+    pub fn get_upgrades(&self, element: Element) -> ElementUpgrades {
+        ElementUpgrades {
+            augmentation: self.augmentations.get(&element).cloned(),
+            aspect: self.enchantments.get(&element).cloned(),
+        }
     }
 
     pub fn get_enchantment(&self, element: Element) -> Option<&Aspect> {
@@ -84,7 +157,7 @@ impl PlayerElements {
         self.augmentations.get(&element)
     }
 
-    fn rock_paper_scissors() -> Self {
+    pub fn rock_paper_scissors() -> Self {
         Self::from_set([Element::Rock, Element::Paper, Element::Scissors])
     }
 }
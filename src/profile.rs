@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::{resource_changed, App, IntoSystemConfigs, Plugin, Res, Resource, Update};
+
+use crate::{storage, Ascension, BonusKind, Element, Mutators, PlayerClass, Relic};
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load());
+        app.add_systems(
+            Update,
+            Self::save_profile.run_if(resource_changed::<Profile>),
+        );
+    }
+}
+
+impl ProfilePlugin {
+    fn save_profile(profile: Res<Profile>) {
+        profile.save();
+    }
+}
+
+/// Player records which persist across runs and launches.
+#[derive(Clone, Debug, Default)]
+#[derive(Deserialize, Serialize)]
+#[derive(Resource)]
+#[serde(default)]
+pub struct Profile {
+    /// The furthest round reached in endless mode.
+    pub best_endless_round: Option<u16>,
+    /// The highest ascension tier unlocked by winning.
+    pub max_ascension: Ascension,
+    /// The ascension tier selected for the next run.
+    pub ascension: Ascension,
+    /// The number of campaigns won.
+    pub wins: u32,
+    /// Every element either player has brought to a battle.
+    pub elements_seen: BTreeSet<Element>,
+    /// How many times each kind of bonus has been chosen.
+    pub bonuses_used: BTreeMap<BonusKind, u32>,
+    /// The class selected for the next run.
+    pub player_class: PlayerClass,
+    /// The ids of every unlocked achievement.
+    pub achievements: BTreeSet<String>,
+    /// The mutators selected for the next run.
+    pub mutators: Mutators,
+}
+
+impl Profile {
+    const STORAGE_KEY: &str = "profile";
+
+    pub fn load() -> Self {
+        storage::load(Self::STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(Self::STORAGE_KEY, self);
+    }
+
+    pub fn record_endless_round(&mut self, round: u16) {
+        if self.best_endless_round.is_none_or(|best| round > best) {
+            self.best_endless_round = Some(round);
+        }
+    }
+
+    /// Counts the win and unlocks the tier above the one that was just won.
+    /// Returns that tier if it was locked until now.
+    pub fn record_victory(&mut self, ascension: Ascension) -> Option<Ascension> {
+        self.wins += 1;
+        let unlocked = ascension.next();
+        if unlocked > self.max_ascension {
+            self.max_ascension = unlocked;
+            Some(unlocked)
+        } else {
+            None
+        }
+    }
+
+    pub fn record_elements(&mut self, elements: impl IntoIterator<Item = Element>) {
+        self.elements_seen.extend(elements);
+    }
+
+    pub fn record_bonus(&mut self, kind: BonusKind) {
+        *self.bonuses_used.entry(kind).or_default() += 1;
+    }
+
+    pub fn total_bonuses_used(&self) -> u32 {
+        self.bonuses_used.values().sum()
+    }
+
+    pub fn select_ascension(&mut self, ascension: Ascension) {
+        self.ascension = ascension.min(self.max_ascension);
+    }
+
+    pub fn is_unlocked(&self, unlock: Unlock) -> bool {
+        Milestone::ALL
+            .iter()
+            .filter(|(_, milestone_unlock)| *milestone_unlock == unlock)
+            .all(|(milestone, _)| milestone.is_reached(self))
+    }
+
+    pub fn unlocked_classes(&self) -> Vec<PlayerClass> {
+        PlayerClass::ALL
+            .into_iter()
+            .filter(|class| self.is_unlocked(Unlock::Class(*class)))
+            .collect()
+    }
+
+    /// Selects the next unlocked class after the current one.
+    pub fn cycle_player_class(&mut self) {
+        let classes = self.unlocked_classes();
+        let index = classes.iter().position(|class| *class == self.player_class);
+        self.player_class = match index {
+            Some(index) => classes[(index + 1) % classes.len()],
+            None => PlayerClass::default(),
+        };
+    }
+
+    pub fn locked_relics(&self) -> BTreeSet<Relic> {
+        Relic::ALL
+            .into_iter()
+            .filter(|relic| !self.is_unlocked(Unlock::Relic(*relic)))
+            .collect()
+    }
+
+    pub fn locked_bonuses(&self) -> BTreeSet<BonusKind> {
+        Milestone::ALL
+            .iter()
+            .filter_map(|(_, unlock)| match unlock {
+                Unlock::Bonus(kind) if !self.is_unlocked(*unlock) => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Content which is unavailable until the profile reaches a [`Milestone`].
+/// Anything without a milestone is available from the start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Unlock {
+    Class(PlayerClass),
+    Relic(Relic),
+    Bonus(BonusKind),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Milestone {
+    Wins(u32),
+    ElementsSeen(usize),
+    BonusesUsed(u32),
+}
+
+impl Milestone {
+    pub const ALL: [(Self, Unlock); 7] = [
+        (
+            Self::BonusesUsed(5),
+            Unlock::Bonus(BonusKind::SwapAugmentation),
+        ),
+        (Self::BonusesUsed(15), Unlock::Relic(Relic::SteadyHands)),
+        (Self::ElementsSeen(7), Unlock::Bonus(BonusKind::Transmute)),
+        (Self::ElementsSeen(7), Unlock::Class(PlayerClass::Warden)),
+        (Self::Wins(1), Unlock::Class(PlayerClass::Elementalist)),
+        (Self::Wins(1), Unlock::Bonus(BonusKind::RemoveElement)),
+        (Self::Wins(2), Unlock::Relic(Relic::Foresight)),
+    ];
+
+    pub fn is_reached(&self, profile: &Profile) -> bool {
+        match self {
+            Milestone::Wins(wins) => profile.wins >= *wins,
+            Milestone::ElementsSeen(count) => profile.elements_seen.len() >= *count,
+            Milestone::BonusesUsed(count) => profile.total_bonuses_used() >= *count,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeSet;
+
+use derive_more::derive::{Deref, DerefMut, Display};
+use rand::{seq::IteratorRandom, RngCore};
+
+use bevy::prelude::Component;
+
+use crate::{Health, MaxHealth, Phase, PlayerOutcome};
+
+/// Relics are passive modifiers which last for the rest of a campaign run.
+/// Each relic hooks into round resolution (see [`Relic::modify_outcome`])
+/// or campaign flow (see [`Relic::on_phase_change`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Display)]
+pub enum Relic {
+    /// The first throw of each game deals 1 additional damage if it wins.
+    #[display("Opening Gambit")]
+    OpeningGambit,
+    /// Heal 2 after each victory.
+    #[display("Second Wind")]
+    SecondWind,
+    /// Combo never drops on draws.
+    #[display("Steady Hands")]
+    SteadyHands,
+    /// Reveals the augmentation of one enemy element.
+    Foresight,
+}
+
+impl Relic {
+    pub const ALL: [Self; 4] = [
+        Self::OpeningGambit,
+        Self::SecondWind,
+        Self::SteadyHands,
+        Self::Foresight,
+    ];
+
+    pub fn random_without(
+        rng: &mut impl RngCore,
+        owned: &Relics,
+        locked: &BTreeSet<Relic>,
+    ) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|relic| !owned.contains(relic) && !locked.contains(relic))
+            .choose(rng)
+    }
+
+    pub fn get_description(&self) -> String {
+        match self {
+            Relic::OpeningGambit => {
+                "The first throw of each game deals 1 additional damage if it wins."
+            }
+            Relic::SecondWind => "Heal 2 after each victory.",
+            Relic::SteadyHands => "Combo never drops on draws.",
+            Relic::Foresight => "Reveals the augmentation of one enemy element each battle.",
+        }
+        .to_string()
+    }
+
+    /// Hook applied to a player's side of the [`crate::Outcome`] after a round is resolved.
+    pub fn modify_outcome(
+        &self,
+        context: &RoundContext,
+        my_outcome: &mut PlayerOutcome,
+        enemy_outcome: &mut PlayerOutcome,
+    ) {
+        match self {
+            Relic::OpeningGambit => {
+                if context.is_first_round && enemy_outcome.damage > my_outcome.damage {
+                    enemy_outcome.damage += 1;
+                }
+            }
+            Relic::SteadyHands => {
+                // a throw can lose yet end even, such as when Combo doubles its damage
+                if context.had_combo && my_outcome.damage == enemy_outcome.damage {
+                    my_outcome.next_combo = Some(crate::Combo);
+                }
+            }
+            Relic::SecondWind | Relic::Foresight => {}
+        }
+    }
+
+    /// Hook applied to the campaign player whenever the campaign [`Phase`] changes.
+    pub fn on_phase_change(&self, phase: &Phase, health: &mut Health, max_health: &MaxHealth) {
+        // every won battle leads to `ProvidingBonus`, even after the campaign's `Victory` when
+        // continuing in endless mode, so healing on `Victory` too would heal twice
+        if let (Relic::SecondWind, Phase::ProvidingBonus) = (self, phase) {
+            health.heal(2, max_health);
+        }
+    }
+}
+
+/// The state of the game that relics may depend on when modifying an outcome.
+#[derive(Clone, Copy, Debug)]
+pub struct RoundContext {
+    pub is_first_round: bool,
+    pub had_combo: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+#[derive(Deref, DerefMut)]
+#[derive(Component)]
+pub struct Relics(pub Vec<Relic>);
+
+impl Relics {
+    pub fn modify_outcome(
+        &self,
+        context: &RoundContext,
+        my_outcome: &mut PlayerOutcome,
+        enemy_outcome: &mut PlayerOutcome,
+    ) {
+        for relic in self.iter() {
+            relic.modify_outcome(context, my_outcome, enemy_outcome);
+        }
+    }
+
+    pub fn on_phase_change(&self, phase: &Phase, health: &mut Health, max_health: &MaxHealth) {
+        for relic in self.iter() {
+            relic.on_phase_change(phase, health, max_health);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use derive_more::derive::{Deref, Display};
+use serde::{Deserialize, Serialize};
+
+use bevy::prelude::Component;
+
+use crate::{Action, Augmentation, Combo, Element, Outcome, PlayerOutcome, Stagger};
+
+/// Which side of a [`Game`] a player is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Display)]
+#[derive(Deserialize, Serialize)]
+pub enum Seat {
+    #[display("Player 1")]
+    PlayerOne,
+    #[display("Player 2")]
+    PlayerTwo,
+}
+
+impl Seat {
+    pub fn opponent(&self) -> Self {
+        match self {
+            Seat::PlayerOne => Seat::PlayerTwo,
+            Seat::PlayerTwo => Seat::PlayerOne,
+        }
+    }
+}
+
+/// One player's half of a resolved round.
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct RoundSide {
+    pub action: Action,
+    /// Damage taken this round. Negative when the player healed.
+    pub damage: i32,
+    /// Health remaining after the round.
+    pub health: u32,
+    /// Whether the player holds Combo going into the next round.
+    pub combo: bool,
+    /// Whether the player is staggered going into the next round.
+    pub staggered: bool,
+}
+
+impl RoundSide {
+    fn new(action: &Action, outcome: &PlayerOutcome, health: u32) -> Self {
+        Self {
+            action: action.clone(),
+            damage: outcome.damage,
+            health,
+            combo: outcome.next_combo.is_some(),
+            staggered: outcome.next_stagger.is_some(),
+        }
+    }
+
+    fn to_player_outcome(&self) -> PlayerOutcome {
+        PlayerOutcome {
+            damage: self.damage,
+            next_combo: self.combo.then_some(Combo),
+            next_stagger: self.staggered.then_some(Stagger),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
+pub struct RoundRecord {
+    pub player_one: RoundSide,
+    pub player_two: RoundSide,
+    /// Whether both players were knocked out and the round is played again.
+    /// Replayed rounds stay in the history but do not count towards round limits or statistics.
+    #[serde(default)]
+    pub replayed: bool,
+}
+
+impl RoundRecord {
+    pub fn new(outcome: &Outcome, p1_health: u32, p2_health: u32) -> Self {
+        Self {
+            player_one: RoundSide::new(&outcome.p1_action, &outcome.p1_outcome, p1_health),
+            player_two: RoundSide::new(&outcome.p2_action, &outcome.p2_outcome, p2_health),
+            replayed: false,
+        }
+    }
+
+    /// Rebuilds the outcome of the round, such as for a round resolved elsewhere.
+    pub fn to_outcome(&self) -> Outcome {
+        Outcome {
+            p1_action: self.player_one.action.clone(),
+            p1_outcome: self.player_one.to_player_outcome(),
+            p2_action: self.player_two.action.clone(),
+            p2_outcome: self.player_two.to_player_outcome(),
+        }
+    }
+
+    pub fn side(&self, seat: Seat) -> &RoundSide {
+        match seat {
+            Seat::PlayerOne => &self.player_one,
+            Seat::PlayerTwo => &self.player_two,
+        }
+    }
+
+    /// Whether `seat` dealt more damage than it took.
+    pub fn is_won_by(&self, seat: Seat) -> bool {
+        self.side(seat.opponent()).damage > self.side(seat).damage
+    }
+}
+
+/// Every round of a game, oldest first. Rounds are only ever appended.
+#[derive(Clone, Debug, Default)]
+#[derive(Deref)]
+#[derive(Deserialize, Serialize)]
+#[derive(Component)]
+pub struct RoundHistory(Vec<RoundRecord>);
+
+impl RoundHistory {
+    pub fn push(&mut self, record: RoundRecord) {
+        self.0.push(record);
+    }
+
+    /// Every round which counts, skipping replayed rounds.
+    pub fn played(&self) -> impl DoubleEndedIterator<Item = &RoundRecord> {
+        self.0.iter().filter(|record| !record.replayed)
+    }
+
+    /// The number of rounds which count towards round limits.
+    pub fn num_played(&self) -> usize {
+        self.played().count()
+    }
+
+    /// The actions `seat` threw in the last `count` rounds, most recent first.
+    pub fn last_throws(&self, seat: Seat, count: usize) -> impl Iterator<Item = &Action> {
+        self.played()
+            .rev()
+            .take(count)
+            .map(move |record| &record.side(seat).action)
+    }
+
+    /// How many times `seat` threw each element.
+    pub fn element_frequency(&self, seat: Seat) -> BTreeMap<Element, usize> {
+        let mut frequency = BTreeMap::new();
+        for record in self.played() {
+            *frequency.entry(record.side(seat).action.guess).or_default() += 1;
+        }
+        frequency
+    }
+
+    /// The number of most recent rounds in a row which satisfy `predicate`.
+    /// The predicate also receives the round before, if any.
+    pub fn streak(&self, predicate: impl Fn(&RoundRecord, Option<&RoundRecord>) -> bool) -> usize {
+        let played = self.played().collect::<Vec<_>>();
+        (0..played.len())
+            .rev()
+            .take_while(|index| {
+                let previous = index.checked_sub(1).map(|previous| played[previous]);
+                predicate(played[*index], previous)
+            })
+            .count()
+    }
+
+    /// The number of rounds in a row `seat` has won.
+    pub fn winning_streak(&self, seat: Seat) -> usize {
+        self.streak(|record, _| record.is_won_by(seat))
+    }
+
+    /// The number of rounds in a row `seat` has thrown the same element.
+    pub fn repeat_streak(&self, seat: Seat) -> usize {
+        let Some(last) = self.played().next_back() else {
+            return 0;
+        };
+        let guess = last.side(seat).action.guess;
+        self.streak(|record, _| record.side(seat).action.guess == guess)
+    }
+
+    /// The number of rounds in a row `seat` has dealt damage with a Combo move while holding Combo.
+    pub fn combo_streak(&self, seat: Seat) -> usize {
+        self.streak(|record, previous| {
+            let side = record.side(seat);
+            previous.is_some_and(|previous| previous.side(seat).combo)
+                && matches!(side.action.augmentation, Some(Augmentation::Combo))
+                && record.side(seat.opponent()).damage > 0
+        })
+    }
+
+    /// The total damage `seat` has taken, ignoring healing.
+    pub fn damage_taken(&self, seat: Seat) -> i32 {
+        self.played()
+            .map(|record| record.side(seat).damage.max(0))
+            .sum()
+    }
+
+    /// Whether `seat` has been staggered at any point.
+    pub fn was_staggered(&self, seat: Seat) -> bool {
+        self.played().any(|record| record.side(seat).staggered)
+    }
+}
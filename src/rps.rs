@@ -4,6 +4,7 @@ use derive_more::derive::{
 use itertools::Itertools;
 use rand::seq::{IteratorRandom, SliceRandom};
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -22,6 +23,7 @@ impl Plugin for RpsPlugin {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[derive(Display)]
+#[derive(Deserialize, Serialize)]
 #[derive(Reflect)]
 pub enum Element {
     Rock,
@@ -136,6 +138,7 @@ impl Guess {
 
 #[derive(Clone, Debug, Default)]
 #[derive(Add, AddAssign, Mul, MulAssign, Sum)]
+#[derive(Deserialize, Serialize)]
 pub struct Payout {
     pub damage_to_me: i32,
     pub damage_to_enemy: i32,
@@ -151,6 +154,7 @@ impl Payout {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deserialize, Serialize)]
 pub struct Action {
     pub guess: Element,
     pub enchantment: Option<Aspect>,
@@ -343,6 +347,7 @@ impl Outcome {
 /// Each element may only have one augmentation.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(Display)]
+#[derive(Deserialize, Serialize)]
 #[derive(Reflect)]
 pub enum Augmentation {
     /// The player takes 1 less HP damage when losing with this move.
@@ -382,6 +387,7 @@ impl Augmentation {
 /// and the sum of the two results is returned.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(Constructor, Deref, DerefMut)]
+#[derive(Deserialize, Serialize)]
 #[derive(Reflect)]
 pub struct Aspect(Element);
 
@@ -405,6 +411,7 @@ pub struct Combo;
 pub struct Stagger;
 
 #[derive(Clone, Debug)]
+#[derive(Deserialize, Serialize)]
 #[derive(Component)]
 pub struct ElementTable(BTreeMap<(Element, Element), Payout>);
 
@@ -0,0 +1,85 @@
+//! Persists small pieces of game state between launches.
+//! Values are stored as RON, in files on native platforms and in `localStorage` on the web.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let contents = backend::read(key)?;
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            bevy::log::warn!("Failed to parse saved {key}: {error}");
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => backend::write(key, &contents),
+        Err(error) => bevy::log::warn!("Failed to serialize {key}: {error}"),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::path::PathBuf;
+
+    const APP_DIRECTORY: &str = "rock-paper-paradise";
+
+    fn directory() -> PathBuf {
+        let base = std::env::var_os("XDG_DATA_HOME")
+            .or_else(|| std::env::var_os("APPDATA"))
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+            .unwrap_or_default();
+        base.join(APP_DIRECTORY)
+    }
+
+    fn path(key: &str) -> PathBuf {
+        directory().join(format!("{key}.ron"))
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        std::fs::read_to_string(path(key)).ok()
+    }
+
+    pub fn write(key: &str, contents: &str) {
+        let result =
+            std::fs::create_dir_all(directory()).and_then(|_| std::fs::write(path(key), contents));
+        if let Err(error) = result {
+            bevy::log::warn!("Failed to save {key}: {error}");
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    const KEY_PREFIX: &str = "rock-paper-paradise";
+
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        storage()?
+            .get_item(&format!("{KEY_PREFIX}/{key}"))
+            .ok()
+            .flatten()
+    }
+
+    pub fn write(key: &str, contents: &str) {
+        let Some(storage) = storage() else {
+            bevy::log::warn!("Failed to save {key}: local storage is unavailable");
+            return;
+        };
+        if storage
+            .set_item(&format!("{KEY_PREFIX}/{key}"), contents)
+            .is_err()
+        {
+            bevy::log::warn!("Failed to save {key}");
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use bevy::{
+    color::palettes::css,
+    ecs::system::StaticSystemParam,
+    prelude::{
+        AlignItems, Alpha, App, BackgroundColor, BuildChildren, ChildBuild, ChildBuilder, Click,
+        Color, Commands, Component, DespawnRecursiveExt, Entity, FlexDirection, JustifyContent,
+        Name, Node, Plugin, Pointer, PositionType, Query, Res, Single, TextColor, TextFont, Time,
+        Timer, TimerMode, Trigger, UiRect, Update, Val, ZIndex,
+    },
+};
+
+use crate::{AchievementUnlocked, CurrentAchievements, Profile};
+
+use super::{text_bundle, AppScreen, UIComponent};
+
+pub struct AchievementUIPlugin;
+
+impl Plugin for AchievementUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(AchievementToast::observer);
+        app.add_systems(Update, AchievementToast::expire_toasts);
+    }
+}
+
+/// A short-lived notification shown when an achievement unlocks.
+#[derive(Component)]
+pub struct AchievementToast(Timer);
+
+impl AchievementToast {
+    const DURATION: Duration = Duration::from_secs(4);
+
+    fn observer(trigger: Trigger<AchievementUnlocked>, mut commands: Commands) {
+        let achievement = &trigger.event().achievement;
+        commands
+            .spawn((
+                Name::new("Achievement Toast"),
+                AchievementToast(Timer::new(Self::DURATION, TimerMode::Once)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(16.),
+                    right: Val::Px(16.),
+                    padding: UiRect::all(Val::Px(12.)),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                BackgroundColor(Color::BLACK.with_alpha(0.8)),
+                ZIndex(100),
+            ))
+            .with_children(|builder| {
+                builder.spawn((
+                    text_bundle(
+                        format!("Achievement unlocked: {}", achievement.name),
+                        TextFont::default(),
+                        Node::default(),
+                    ),
+                    TextColor(css::GOLD.into()),
+                ));
+                builder.spawn(text_bundle(
+                    achievement.description.clone(),
+                    TextFont {
+                        font_size: 14.,
+                        ..Default::default()
+                    },
+                    Node::default(),
+                ));
+            });
+    }
+
+    fn expire_toasts(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut toasts: Query<(Entity, &mut AchievementToast)>,
+    ) {
+        for (entity, mut toast) in &mut toasts {
+            if toast.0.tick(time.delta()).finished() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Lists every achievement and whether it has been unlocked.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct AchievementsUIComponent;
+
+impl UIComponent for AchievementsUIComponent {
+    type Params = (CurrentAchievements<'static>, Res<'static, Profile>);
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, params: &StaticSystemParam<Self::Params>) {
+        let (achievements, profile) = &**params;
+        let achievements = achievements
+            .get()
+            .map(|list| list.achievements.clone())
+            .unwrap_or_default();
+        let num_unlocked = achievements
+            .iter()
+            .filter(|achievement| profile.achievements.contains(&achievement.id))
+            .count();
+
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                row_gap: Val::Px(8.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    format!("Achievements ({num_unlocked}/{})", achievements.len()),
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                for achievement in &achievements {
+                    let is_unlocked = profile.achievements.contains(&achievement.id);
+                    builder.spawn((
+                        text_bundle(
+                            format!("{} - {}", achievement.name, achievement.description),
+                            TextFont {
+                                font_size: 14.,
+                                ..Default::default()
+                            },
+                            Node::default(),
+                        ),
+                        TextColor(if is_unlocked {
+                            css::GOLD.into()
+                        } else {
+                            css::GRAY.into()
+                        }),
+                    ));
+                }
+                builder
+                    .spawn(text_bundle(
+                        "Back",
+                        TextFont::default(),
+                        Node {
+                            margin: UiRect::top(Val::Px(16.)),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut AppScreen>| {
+                            **app_screen = AppScreen::HomeMenu;
+                        },
+                    );
+            });
+    }
+}
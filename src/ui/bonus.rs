@@ -4,24 +4,35 @@ use bevy::{
         AlignItems, Alpha, BackgroundColor, BorderColor, BuildChildren, ChildBuild, ChildBuilder,
         Click, Color, Commands, Component, DespawnRecursiveExt, Entity, FlexDirection, FlexWrap,
         JustifyContent, Name, Node, Out, Over, Pointer, PositionType, Query, Res, Single, Text,
-        TextFont, Trigger, UiRect, Val, With,
+        TextColor, TextFont, Trigger, UiRect, Val, With, ZIndex,
     },
 };
 
-use crate::{Bonus, Campaign, Element, ElementTable, Level, Phase, PlayerElements, Upgrades};
+use crate::{
+    BanishBonus, Bonus, BonusActions, BonusOffer, ChooseBonus, ChooseEvolution, Element,
+    ElementTable, PlayerElements, RerollUpgrades, SkipBonus, UpgradeConflict, Upgrades,
+};
 
-use super::{image_bundle, text_bundle, ElementTooltip, LocalPlayer, RpsGlyphs, UIComponent};
+use super::{
+    image_bundle, text_bundle, AppScreen, ElementTooltip, LocalPlayer, RelicTooltip, RpsGlyphs,
+    UIComponent,
+};
 
 #[derive(Clone, Debug)]
 #[derive(Component)]
 pub struct BonusUIComponent {
     upgrades: Upgrades,
     table: ElementTable,
+    actions: BonusActions,
 }
 
 impl BonusUIComponent {
-    pub fn new(table: ElementTable, upgrades: Upgrades) -> Self {
-        Self { table, upgrades }
+    pub fn new(table: ElementTable, upgrades: Upgrades, actions: BonusActions) -> Self {
+        Self {
+            table,
+            upgrades,
+            actions,
+        }
     }
 }
 
@@ -123,29 +134,107 @@ impl UIComponent for BonusUIComponent {
                         ..Default::default()
                     },
                 ));
+                if let Some(relic) = self.upgrades.relic {
+                    builder.spawn(text_bundle(
+                        "Relic acquired:",
+                        TextFont::default(),
+                        Node {
+                            margin: UiRect::top(Val::Px(12.)),
+                            ..Default::default()
+                        },
+                    ));
+                    RelicTooltip::spawn_relic(builder, relic);
+                }
             });
 
+        // an evolution must be chosen, so neither skipping nor rerolling is allowed
+        let is_evolution_forced =
+            self.upgrades.bonuses.is_empty() && !self.upgrades.evolutions.is_empty();
+
         builder
             .spawn((BonusSelectionPanel, BonusSelectionPanel::node()))
             .with_children(|builder| {
-                for (bonus, bonus_element) in self.upgrades.bonuses {
+                for (
+                    index,
+                    BonusOffer {
+                        bonus,
+                        element: bonus_element,
+                        rarity,
+                    },
+                ) in self.upgrades.bonuses.into_iter().enumerate()
+                {
                     let bonus_name = bonus.get_readable_name();
-                    let bonus_description = bonus.get_description(bonus_element);
+                    let bonus_description =
+                        format!("({rarity}) {}", bonus.get_description(bonus_element));
                     builder
-                        .spawn((
-                            BonusButton,
-                            super::text_bundle(
-                                format!("{bonus_element}: {}", bonus_name.clone()),
-                                TextFont::default(),
-                                BonusButton::node(),
-                            ),
-                        ))
+                        .spawn((BonusButton, BonusButton::node()))
+                        .with_children(|builder| {
+                            builder.spawn((
+                                text_bundle(
+                                    format!("{bonus_element}: {}", bonus_name.clone()),
+                                    TextFont::default(),
+                                    Node::default(),
+                                ),
+                                TextColor(rarity.primary_color()),
+                            ));
+                            if self.actions.banishes > 0 {
+                                builder
+                                    .spawn(text_bundle(
+                                        "Banish",
+                                        TextFont {
+                                            font_size: 14.,
+                                            ..Default::default()
+                                        },
+                                        Node::default(),
+                                    ))
+                                    .observe(BonusActionsPanel::make_on_banish(index));
+                            }
+                        })
                         .observe(BonusButton::make_on_click(bonus, bonus_element))
                         .observe(BonusTooltip::make_on_over(bonus_description))
                         .observe(BonusTooltip::make_on_out());
                 }
             });
 
+        if !is_evolution_forced {
+            builder
+                .spawn((BonusActionsPanel, BonusActionsPanel::node()))
+                .with_children(|builder| {
+                    if self.actions.rerolls > 0 {
+                        builder
+                            .spawn(text_bundle(
+                                format!("Reroll ({} left)", self.actions.rerolls),
+                                TextFont::default(),
+                                Node::default(),
+                            ))
+                            .observe(
+                                |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                    commands.trigger(RerollUpgrades);
+                                },
+                            );
+                    }
+                    builder
+                        .spawn(text_bundle(
+                            format!("Skip (+{} HP)", BonusActions::SKIP_HEAL),
+                            TextFont::default(),
+                            Node::default(),
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                commands.trigger(SkipBonus);
+                            },
+                        );
+                    builder.spawn(text_bundle(
+                        format!("Banishes left: {}", self.actions.banishes),
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Node::default(),
+                    ));
+                });
+        }
+
         builder
             .spawn((EvolutionSelectionPanel, EvolutionSelectionPanel::node()))
             .with_children(|builder| {
@@ -243,6 +332,35 @@ impl BonusSelectionPanel {
     }
 }
 
+#[derive(Component)]
+pub struct BonusActionsPanel;
+
+impl BonusActionsPanel {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Auto,
+            left: Val::Percent(40.),
+            right: Val::Auto,
+            bottom: Val::Percent(10.),
+            height: Val::Px(120.),
+            width: Val::Px(200.),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(12.),
+            ..Default::default()
+        }
+    }
+
+    fn make_on_banish(index: usize) -> impl FnMut(Trigger<Pointer<Click>>, Commands) {
+        move |mut trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            // don't let the click reach the bonus button itself
+            trigger.propagate(false);
+            commands.trigger(BanishBonus { index });
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct BonusButton;
 
@@ -253,6 +371,8 @@ impl BonusButton {
             height: Val::Percent(100.),
             flex_grow: 0.,
             flex_shrink: 0.,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.),
             ..Default::default()
         }
     }
@@ -263,16 +383,119 @@ impl BonusButton {
         bonus_element: Element,
     ) -> impl FnMut(
         Trigger<Pointer<Click>>,
-        Single<(&Campaign, &mut ElementTable, &mut Phase)>,
-        Single<&mut PlayerElements, With<LocalPlayer>>,
+        Commands,
+        Single<Entity, With<AppScreen>>,
+        Single<&PlayerElements, With<LocalPlayer>>,
     ) {
         move |_trigger: Trigger<Pointer<Click>>,
-              mut campaign: Single<(&Campaign, &mut ElementTable, &mut Phase)>,
-              mut local_player: Single<&mut PlayerElements, With<LocalPlayer>>| {
-            bonus.update_game(&mut campaign.1, &mut local_player, bonus_element);
-            *campaign.2 = Phase::InGame;
+              mut commands: Commands,
+              root: Single<Entity, With<AppScreen>>,
+              local_player: Single<&PlayerElements, With<LocalPlayer>>| {
+            if let Some(conflict) = bonus.conflict(&local_player, bonus_element) {
+                commands.entity(*root).with_children(|builder| {
+                    UpgradeConflictPanel::spawn(builder, bonus.clone(), bonus_element, conflict);
+                });
+            } else {
+                commands.trigger(ChooseBonus {
+                    bonus: bonus.clone(),
+                    element: bonus_element,
+                });
+            }
+        }
+    }
+}
+
+/// Asks the player to confirm a bonus which would replace one of their existing upgrades.
+#[derive(Component)]
+pub struct UpgradeConflictPanel;
+
+impl UpgradeConflictPanel {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(35.),
+            left: Val::Percent(30.),
+            right: Val::Percent(30.),
+            bottom: Val::Auto,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(12.),
+            padding: UiRect::all(Val::Px(16.)),
+            border: UiRect::all(Val::Px(2.)),
+            ..Default::default()
         }
     }
+
+    pub fn background_color() -> BackgroundColor {
+        use bevy::color::palettes::css;
+        css::DARK_SLATE_BLUE.with_alpha(0.95).into()
+    }
+
+    fn spawn(
+        builder: &mut ChildBuilder<'_>,
+        bonus: Bonus,
+        bonus_element: Element,
+        conflict: UpgradeConflict,
+    ) {
+        builder
+            .spawn((
+                UpgradeConflictPanel,
+                Self::node(),
+                Self::background_color(),
+                BorderColor(Color::BLACK),
+                ZIndex(1),
+            ))
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    format!(
+                        "{} will replace the upgrades of {}.",
+                        bonus.get_readable_name(),
+                        conflict.element
+                    ),
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder.spawn(text_bundle(
+                    format!("Current: {}", conflict.current),
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder.spawn(text_bundle(
+                    format!("New: {}", conflict.new),
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder
+                    .spawn(Node {
+                        column_gap: Val::Px(40.),
+                        ..Default::default()
+                    })
+                    .with_children(|builder| {
+                        builder
+                            .spawn(text_bundle("Confirm", TextFont::default(), Node::default()))
+                            .observe(
+                                move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                    commands.trigger(ChooseBonus {
+                                        bonus: bonus.clone(),
+                                        element: bonus_element,
+                                    });
+                                },
+                            );
+                        builder
+                            .spawn(text_bundle("Cancel", TextFont::default(), Node::default()))
+                            .observe(
+                                |_trigger: Trigger<Pointer<Click>>,
+                                 mut commands: Commands,
+                                 panels: Query<Entity, With<UpgradeConflictPanel>>| {
+                                    for panel in &panels {
+                                        commands.entity(panel).despawn_recursive();
+                                    }
+                                },
+                            );
+                    });
+            });
+    }
 }
 
 #[derive(Component)]
@@ -370,20 +593,9 @@ impl EvolutionButton {
         }
     }
 
-    #[allow(clippy::type_complexity)]
-    fn make_on_click(
-        element: Element,
-    ) -> impl FnMut(
-        Trigger<Pointer<Click>>,
-        Single<(&mut Phase, &mut Level), With<Campaign>>,
-        Single<&mut PlayerElements, With<LocalPlayer>>,
-    ) {
-        move |_trigger: Trigger<Pointer<Click>>,
-              mut campaign: Single<(&mut Phase, &mut Level), With<Campaign>>,
-              mut local_player: Single<&mut PlayerElements, With<LocalPlayer>>| {
-            local_player.insert(element);
-            campaign.1.increment();
-            *campaign.0 = Phase::InGame;
+    fn make_on_click(element: Element) -> impl FnMut(Trigger<Pointer<Click>>, Commands) {
+        move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.trigger(ChooseEvolution { element });
         }
     }
 }
@@ -1,21 +1,25 @@
 use std::collections::BTreeSet;
 
 use bevy::{
+    color::palettes::css,
     ecs::system::StaticSystemParam,
     prelude::{
         AlignItems, Alpha, App, BackgroundColor, BorderColor, BorderRadius, BuildChildren, Changed,
         ChildBuild, ChildBuilder, Click, Color, Commands, Component, DespawnRecursiveExt, Entity,
-        FlexDirection, FlexWrap, IntoSystemConfigs, JustifyContent, Name, Node, Out, Over, Plugin,
-        Pointer, PositionType, Query, RemovedComponents, Res, Single, Text, TextFont, Trigger,
-        UiRect, Update, Val, With, Without, ZIndex,
+        FlexDirection, FlexWrap, ImageNode, IntoSystemConfigs, JustifyContent, Name, Node, Out,
+        Over, Plugin, Pointer, PositionType, Query, RemovedComponents, Res, Single, Text,
+        TextColor, TextFont, Trigger, UiRect, Update, Val, With, Without, ZIndex,
     },
 };
 
 use crate::{
-    Action, Element, ElementTable, Game, Guess, Health, LastOutcome, PlayerElements, Round,
+    Action, Element, ElementTable, Encounter, ForcedAction, Game, Guess, Health, LastOutcome,
+    LevelProgress, PlayerElements, Relic, Round, RoundHistory, Seat, ThrowTimer, Tiebroken,
 };
 
-use super::{image_bundle, text_bundle, ElementTooltip, LocalPlayer, RpsGlyphs, UIComponent};
+use super::{
+    image_bundle, text_bundle, ElementTooltip, LocalPlayer, RelicsPanel, RpsGlyphs, UIComponent,
+};
 
 pub struct GameUIPlugin;
 
@@ -26,8 +30,11 @@ impl Plugin for GameUIPlugin {
             (
                 Self::track_player_hp,
                 Self::track_enemy_hp,
+                Self::track_throw_timer,
+                Self::track_forced_action,
                 Self::cleanup_last_outcome_ui,
                 Self::track_last_outcome,
+                Self::track_round_log,
             )
                 .chain(),
         );
@@ -61,10 +68,51 @@ impl GameUIPlugin {
         text.0 = EnemyHPNode::text(&hp);
     }
 
+    /// Locks every button except the forced one while the local player is staggered.
+    #[allow(clippy::type_complexity)]
+    fn track_forced_action(
+        local_player: Option<Single<Option<&ForcedAction>, With<LocalPlayer>>>,
+        mut buttons: Query<(&ThrowButton, &mut ImageNode, &mut BorderColor)>,
+        banner: Option<Single<&mut Text, With<StaggerBanner>>>,
+    ) {
+        let Some(forced_action) = local_player else {
+            return;
+        };
+        let forced = forced_action.map(|ForcedAction(element)| *element);
+        for (ThrowButton(element), mut image, mut border_color) in &mut buttons {
+            let is_locked = forced.is_some_and(|forced| forced != *element);
+            image.color = Color::WHITE.with_alpha(if is_locked { 0.25 } else { 1. });
+            border_color.0 = if forced == Some(*element) {
+                css::GOLD.into()
+            } else {
+                Color::NONE
+            };
+        }
+        if let Some(mut text) = banner {
+            text.0 = forced.map(StaggerBanner::text).unwrap_or_default();
+        }
+    }
+
+    fn track_throw_timer(
+        throw_timer_ui: Option<Single<&mut Text, With<ThrowTimerNode>>>,
+        throw_timer: Option<Single<&ThrowTimer, With<Game>>>,
+    ) {
+        let Some(mut text) = throw_timer_ui else {
+            return;
+        };
+        let Some(throw_timer) = throw_timer else {
+            return;
+        };
+        text.0 = ThrowTimerNode::text(&throw_timer);
+    }
+
+    #[allow(clippy::type_complexity)]
     fn track_last_outcome(
         mut commands: Commands,
         last_outcome_ui: Option<Single<Entity, With<LastOutcomePanel>>>,
-        updated_game: Option<Single<&LastOutcome, (With<Game>, Changed<LastOutcome>)>>,
+        updated_game: Option<
+            Single<(&LastOutcome, Option<&Tiebroken>), (With<Game>, Changed<LastOutcome>)>,
+        >,
         glyphs: Res<RpsGlyphs>,
     ) {
         let Some(ui) = last_outcome_ui else {
@@ -73,11 +121,21 @@ impl GameUIPlugin {
         let Some(game) = updated_game else {
             return;
         };
-        let last_outcome = *game;
+        let (last_outcome, tiebroken) = *game;
         commands
             .entity(*ui)
             .despawn_descendants()
             .with_children(|builder| {
+                if let Some(Tiebroken(tiebreak)) = tiebroken {
+                    builder.spawn((
+                        text_bundle(
+                            tiebreak.get_description(),
+                            TextFont::default(),
+                            Node::default(),
+                        ),
+                        TextColor(css::GOLD.into()),
+                    ));
+                }
                 builder
                     .spawn(LastOutcomePanel::elements_row())
                     .with_child(text_bundle(
@@ -113,6 +171,55 @@ impl GameUIPlugin {
             });
     }
 
+    fn track_round_log(
+        mut commands: Commands,
+        round_log_ui: Option<Single<Entity, With<RoundLogPanel>>>,
+        updated_game: Option<Single<(&Game, &RoundHistory), Changed<RoundHistory>>>,
+        local_player: Option<Single<Entity, With<LocalPlayer>>>,
+    ) {
+        let Some(ui) = round_log_ui else {
+            return;
+        };
+        let Some(game) = updated_game else {
+            return;
+        };
+        let Some(local_player) = local_player else {
+            return;
+        };
+        let (game, history) = *game;
+        let Some(seat) = game.seat(*local_player) else {
+            return;
+        };
+        commands
+            .entity(*ui)
+            .despawn_descendants()
+            .with_children(|builder| {
+                for (index, record) in history
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .take(RoundLogPanel::MAX_ROUNDS)
+                {
+                    let (mine, theirs) = (record.side(seat), record.side(seat.opponent()));
+                    builder.spawn(text_bundle(
+                        format!(
+                            "{}. {} vs {} ({} / {})",
+                            index + 1,
+                            mine.action.guess,
+                            theirs.action.guess,
+                            -mine.damage,
+                            -theirs.damage,
+                        ),
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Node::default(),
+                    ));
+                }
+            });
+    }
+
     fn cleanup_last_outcome_ui(
         mut commands: Commands,
         mut removed_outcome: RemovedComponents<LastOutcome>,
@@ -133,8 +240,18 @@ impl GameUIPlugin {
 pub struct GameUIComponent {
     pub player_elements: PlayerElements,
     pub enemy_elements: PlayerElements,
+    /// Enemy elements whose augmentations are visible to the player.
+    pub revealed_enemy_elements: BTreeSet<Element>,
+    pub relics: Vec<Relic>,
     pub table: ElementTable,
     pub round: Round,
+    pub encounter: Encounter,
+    /// Where the battle sits within the campaign, if it is not endless.
+    pub progress: Option<LevelProgress>,
+    /// The player's seat when playing against another person rather than an encounter.
+    pub seat: Option<Seat>,
+    /// Watching a game between two other people from `player`'s side, without throwing for them.
+    pub spectating: bool,
     pub player: Entity,
 }
 
@@ -147,7 +264,10 @@ impl UIComponent for GameUIComponent {
             .with_children(|builder| {
                 for element in &self.enemy_elements.elements {
                     let element = *element;
-                    let augmentation = self.enemy_elements.get_augmentation(element);
+                    let augmentation = self
+                        .enemy_elements
+                        .get_augmentation(element)
+                        .filter(|_| self.revealed_enemy_elements.contains(&element));
                     let aspect = self.enemy_elements.get_enchantment(element);
 
                     builder
@@ -179,22 +299,34 @@ impl UIComponent for GameUIComponent {
                     let element = *element;
                     let augmentation = self.player_elements.get_augmentation(element);
                     let aspect = self.player_elements.get_enchantment(element);
-
+                    let image = params
+                        .get_image(&Action::new(
+                            element,
+                            aspect.cloned(),
+                            augmentation.cloned(),
+                        ))
+                        .unwrap()
+                        .clone();
+
+                    if self.spectating {
+                        builder
+                            .spawn((
+                                GameButton,
+                                GameButton::radius(),
+                                GameButton::background_color(),
+                                super::image_bundle(image, GameButton::node()),
+                            ))
+                            .observe(ElementTooltip::make_on_over(element))
+                            .observe(ElementTooltip::make_on_out());
+                        continue;
+                    }
                     let mut button = builder.spawn((
                         GameButton,
+                        ThrowButton(element),
+                        BorderColor(Color::NONE),
                         GameButton::radius(),
                         GameButton::background_color(),
-                        super::image_bundle(
-                            params
-                                .get_image(&Action::new(
-                                    element,
-                                    aspect.cloned(),
-                                    augmentation.cloned(),
-                                ))
-                                .unwrap()
-                                .clone(),
-                            GameButton::node(),
-                        ),
+                        super::image_bundle(image, GameButton::node()),
                     ));
                     button
                         .observe(GameButton::make_on_click(self.player, element))
@@ -217,7 +349,8 @@ impl UIComponent for GameUIComponent {
             ))
             .with_children(|builder| {
                 builder.spawn(text_bundle(
-                    "Player",
+                    self.seat
+                        .map_or("Player".to_string(), |seat| seat.to_string()),
                     Default::default(),
                     Default::default(),
                 ));
@@ -225,6 +358,10 @@ impl UIComponent for GameUIComponent {
                     PlayerHPNode,
                     text_bundle("HP: ", Default::default(), Default::default()),
                 ));
+                builder.spawn((
+                    ThrowTimerNode,
+                    text_bundle("", Default::default(), Default::default()),
+                ));
             });
         builder
             .spawn((
@@ -233,18 +370,53 @@ impl UIComponent for GameUIComponent {
                 EnemyStatsPanel::border_color(),
             ))
             .with_children(|builder| {
+                let enemy_name = match self.encounter {
+                    Encounter::Battle => "Enemy",
+                    Encounter::Elite => "Elite",
+                    Encounter::Boss => "Boss",
+                };
                 builder.spawn(text_bundle(
-                    format!("Enemy (Round {})", *self.round),
+                    match self.seat {
+                        Some(seat) => seat.opponent().to_string(),
+                        None => format!("{enemy_name} (Round {})", *self.round),
+                    },
                     Default::default(),
                     Default::default(),
                 ));
+                if let Some(progress) = &self.progress {
+                    builder.spawn(text_bundle(
+                        progress.to_string(),
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Default::default(),
+                    ));
+                }
                 builder.spawn((
                     EnemyHPNode,
                     text_bundle("HP:", Default::default(), Default::default()),
                 ));
             });
 
+        RelicsPanel::spawn(builder, &self.relics);
+
         builder.spawn((LastOutcomePanel, LastOutcomePanel::node(), ZIndex(-1)));
+
+        builder.spawn((RoundLogPanel, RoundLogPanel::node()));
+
+        if self.spectating {
+            builder.spawn(StaggerBanner::node()).with_child((
+                text_bundle("Spectating", TextFont::default(), Node::default()),
+                TextColor(css::GRAY.into()),
+            ));
+        } else {
+            builder.spawn(StaggerBanner::node()).with_child((
+                StaggerBanner,
+                text_bundle("", TextFont::default(), Node::default()),
+                TextColor(css::GOLD.into()),
+            ));
+        }
     }
 }
 
@@ -318,8 +490,16 @@ impl GameButton {
     fn make_on_click(
         player: Entity,
         guess: Element,
-    ) -> impl FnMut(Trigger<Pointer<Click>>, Commands) {
-        move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+    ) -> impl FnMut(Trigger<Pointer<Click>>, Commands, Query<&ForcedAction>) {
+        move |_trigger: Trigger<Pointer<Click>>,
+              mut commands: Commands,
+              forced_actions: Query<&ForcedAction>| {
+            if forced_actions
+                .get(player)
+                .is_ok_and(|ForcedAction(forced)| *forced != guess)
+            {
+                return;
+            }
             commands.entity(player).insert(Guess::new(guess));
         }
     }
@@ -430,6 +610,52 @@ impl EnemyStatsPanel {
     }
 }
 
+/// One of the local player's buttons, which throws `0`.
+#[derive(Component)]
+struct ThrowButton(Element);
+
+/// Tells the local player which element they must repeat while staggered.
+#[derive(Component)]
+struct StaggerBanner;
+
+impl StaggerBanner {
+    fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.),
+            right: Val::Px(0.),
+            bottom: Val::Percent(25.),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        }
+    }
+
+    fn text(element: Element) -> String {
+        format!("Staggered: must repeat {element}")
+    }
+}
+
+/// The most recent rounds of the game, newest first.
+#[derive(Component)]
+struct RoundLogPanel;
+
+impl RoundLogPanel {
+    const MAX_ROUNDS: usize = 5;
+
+    fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(20.),
+            left: Val::Px(100.),
+            right: Val::Auto,
+            bottom: Val::Auto,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct PlayerHPNode;
 
@@ -439,6 +665,16 @@ impl PlayerHPNode {
     }
 }
 
+/// The time left to throw, if the game has a throw timer.
+#[derive(Component)]
+pub struct ThrowTimerNode;
+
+impl ThrowTimerNode {
+    fn text(throw_timer: &ThrowTimer) -> String {
+        format!("Time: {}s", throw_timer.seconds_left())
+    }
+}
+
 #[derive(Component)]
 pub struct EnemyHPNode;
 
@@ -467,7 +703,6 @@ impl PayoutTooltip {
     }
 
     fn background_color() -> BackgroundColor {
-        use bevy::color::palettes::css;
         css::DARK_SLATE_BLUE.with_alpha(0.95).into()
     }
 }
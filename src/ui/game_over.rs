@@ -3,45 +3,77 @@ use bevy::{
     ecs::system::StaticSystemParam,
     prelude::{
         BuildChildren, ChildBuild, ChildBuilder, Click, Commands, Component, Entity, Node, Pointer,
-        TextFont, Trigger, Val,
+        Res, Single, TextFont, Trigger, Val,
     },
     ui::{BorderColor, FlexDirection, JustifyContent, PositionType},
 };
 
-use crate::{Level, Round, SpawnCampaign};
+use crate::{Ascension, Level, Profile, Round, SpawnCampaign, StartEndless};
 
-use super::{text_bundle, UIComponent};
+use super::{text_bundle, AppScreen, UIComponent};
 
 #[derive(Clone, Debug)]
 #[derive(Component)]
 pub struct GameOverUIComponent {
     is_victory: bool,
+    is_endless: bool,
+    best_endless_round: Option<u16>,
     round: Round,
     level: Level,
+    ascension: Ascension,
+    /// The ascension tier this victory unlocked, if it was locked until now.
+    unlocked_ascension: Option<Ascension>,
     campaign: Entity,
     player: Entity,
 }
 
 impl GameOverUIComponent {
-    pub fn victory(campaign: Entity, player: Entity, round: Round, level: Level) -> Self {
+    pub fn victory(
+        campaign: Entity,
+        player: Entity,
+        round: Round,
+        level: Level,
+        ascension: Ascension,
+        unlocked_ascension: Option<Ascension>,
+    ) -> Self {
         Self {
             is_victory: true,
+            is_endless: false,
+            best_endless_round: None,
             campaign,
             player,
             round,
             level,
+            ascension,
+            unlocked_ascension,
         }
     }
 
-    pub fn loss(campaign: Entity, player: Entity, round: Round, level: Level) -> Self {
+    pub fn loss(
+        campaign: Entity,
+        player: Entity,
+        round: Round,
+        level: Level,
+        ascension: Ascension,
+    ) -> Self {
         Self {
             is_victory: false,
+            is_endless: false,
+            best_endless_round: None,
             campaign,
             player,
             round,
             level,
+            ascension,
+            unlocked_ascension: None,
         }
     }
+
+    pub fn with_endless(mut self, best_endless_round: Option<u16>) -> Self {
+        self.is_endless = true;
+        self.best_endless_round = best_endless_round;
+        self
+    }
 }
 
 impl UIComponent for GameOverUIComponent {
@@ -55,7 +87,13 @@ impl UIComponent for GameOverUIComponent {
                     .spawn((GameOverUIPanel::inner_node(), BorderColor(Color::BLACK)))
                     .with_children(|builder| {
                         builder.spawn(text_bundle(
-                            if self.is_victory {
+                            if self.is_endless {
+                                format!(
+                                    "You reached round {:} in endless mode. (Best: {:})",
+                                    *self.round,
+                                    self.best_endless_round.unwrap_or(*self.round),
+                                )
+                            } else if self.is_victory {
                                 format!(
                                     "You win! You defeated the game in {:} battles.",
                                     *self.round
@@ -69,11 +107,43 @@ impl UIComponent for GameOverUIComponent {
                             TextFont::default(),
                             Node::default(),
                         ));
+                        if *self.ascension > 0 {
+                            builder.spawn(text_bundle(
+                                format!("Ascension {:}", *self.ascension),
+                                TextFont::default(),
+                                Node::default(),
+                            ));
+                        }
+                        if let Some(unlocked_ascension) = self.unlocked_ascension {
+                            builder.spawn(text_bundle(
+                                format!("Ascension {:} unlocked!", *unlocked_ascension),
+                                TextFont::default(),
+                                Node::default(),
+                            ));
+                        }
+                        if self.is_victory {
+                            builder
+                                .spawn(text_bundle(
+                                    "Continue in endless mode",
+                                    TextFont::default(),
+                                    Node::default(),
+                                ))
+                                .observe(
+                                    |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                        commands.trigger(StartEndless);
+                                    },
+                                );
+                        }
                         let handle_click =
-                            move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                            move |_trigger: Trigger<Pointer<Click>>,
+                                  mut commands: Commands,
+                                  profile: Res<Profile>| {
                                 commands.entity(self.campaign).despawn();
                                 commands.trigger(SpawnCampaign {
                                     player: Some(self.player),
+                                    ascension: self.ascension,
+                                    player_class: profile.player_class,
+                                    mutators: profile.mutators.clone(),
                                 });
                             };
                         builder
@@ -83,6 +153,17 @@ impl UIComponent for GameOverUIComponent {
                                 Node::default(),
                             ))
                             .observe(handle_click);
+                        builder
+                            .spawn(text_bundle("Home", TextFont::default(), Node::default()))
+                            .observe(
+                                move |_trigger: Trigger<Pointer<Click>>,
+                                      mut commands: Commands,
+                                      mut app_screen: Single<&mut AppScreen>| {
+                                    commands.entity(self.campaign).despawn();
+                                    commands.entity(self.player).despawn();
+                                    **app_screen = AppScreen::HomeMenu;
+                                },
+                            );
                     });
             });
     }
@@ -0,0 +1,149 @@
+use bevy::{
+    ecs::system::StaticSystemParam,
+    prelude::{
+        AlignItems, BuildChildren, ChildBuild, ChildBuilder, Click, Component, FlexDirection,
+        JustifyContent, Node, Pointer, Res, Single, TextFont, Trigger, UiRect, Val,
+    },
+};
+
+use crate::{RunHistory, RunRecord, RunResult};
+
+use super::{text_bundle, AppScreen, UIComponent};
+
+/// Lists recent runs along with statistics over every recorded run.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct HistoryUIComponent;
+
+impl HistoryUIComponent {
+    const RECENT_RUNS: usize = 10;
+
+    fn run_text(record: &RunRecord) -> String {
+        let result = match &record.result {
+            Some(RunResult::Victory) => "Victory".to_string(),
+            Some(RunResult::Defeat(cause)) => cause.to_string(),
+            None => "Abandoned".to_string(),
+        };
+        let endless = record
+            .endless_round
+            .map(|round| format!(", endless round {round}"))
+            .unwrap_or_default();
+        let mutators = if record.is_ranked() {
+            String::default()
+        } else {
+            format!(" [{}]", record.mutators)
+        };
+        format!(
+            "{} (Ascension {}){mutators} - {} battles{endless} - {result} - seed {:x}",
+            record.player_class, *record.ascension, record.rounds, record.seed,
+        )
+    }
+}
+
+impl UIComponent for HistoryUIComponent {
+    type Params = Res<'static, RunHistory>;
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, params: &StaticSystemParam<Self::Params>) {
+        let history = &**params;
+        let stats = history.stats();
+        let small_font = TextFont {
+            font_size: 14.,
+            ..Default::default()
+        };
+
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(24.)),
+                row_gap: Val::Px(8.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    "Run History",
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder.spawn(text_bundle(
+                    format!(
+                        "{} runs, {} wins ({:.0}%), {:.1} battles on average",
+                        stats.runs,
+                        stats.wins,
+                        stats.win_rate() * 100.,
+                        stats.average_rounds(),
+                    ),
+                    small_font.clone(),
+                    Node::default(),
+                ));
+                for (player_class, (runs, wins)) in &stats.classes {
+                    builder.spawn(text_bundle(
+                        format!("{player_class}: {wins} wins in {runs} runs"),
+                        small_font.clone(),
+                        Node::default(),
+                    ));
+                }
+                if let Some(element) = stats.favorite_element() {
+                    builder.spawn(text_bundle(
+                        format!("Favorite element: {element}"),
+                        small_font.clone(),
+                        Node::default(),
+                    ));
+                }
+                for (element, win_rate) in stats.element_win_rates() {
+                    builder.spawn(text_bundle(
+                        format!("{element}: {:.0}% of throws won", win_rate * 100.),
+                        small_font.clone(),
+                        Node::default(),
+                    ));
+                }
+                if let Some(bonus) = stats.favorite_bonus() {
+                    builder.spawn(text_bundle(
+                        format!("Favorite bonus: {bonus}"),
+                        small_font.clone(),
+                        Node::default(),
+                    ));
+                }
+                builder.spawn(text_bundle(
+                    "Recent runs",
+                    TextFont::default(),
+                    Node {
+                        margin: UiRect::top(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                ));
+                if history.is_empty() {
+                    builder.spawn(text_bundle(
+                        "No runs yet",
+                        small_font.clone(),
+                        Node::default(),
+                    ));
+                }
+                for record in history.iter().rev().take(Self::RECENT_RUNS) {
+                    builder.spawn(text_bundle(
+                        Self::run_text(record),
+                        small_font.clone(),
+                        Node::default(),
+                    ));
+                }
+                builder
+                    .spawn(text_bundle(
+                        "Back",
+                        TextFont::default(),
+                        Node {
+                            margin: UiRect::top(Val::Px(16.)),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut AppScreen>| {
+                            **app_screen = AppScreen::HomeMenu;
+                        },
+                    );
+            });
+    }
+}
@@ -2,26 +2,28 @@ use bevy::{
     ecs::system::StaticSystemParam,
     prelude::{
         AlignItems, AssetServer, BuildChildren, ChildBuild, ChildBuilder, Click, Commands,
-        Component, FlexDirection, JustifyContent, Node, Pointer, Res, TextFont, Trigger, UiRect,
-        Val,
+        Component, FlexDirection, JustifyContent, Node, Pointer, Res, ResMut, Single, Text,
+        TextFont, Trigger, UiRect, Val, With, Without,
     },
 };
 
-use crate::SpawnCampaign;
+use crate::{Ascension, PlayerClass, Profile, SpawnCampaign};
 
-use super::UIComponent;
+use super::{MutatorsUIComponent, UIComponent};
 
 #[derive(Clone, Debug)]
 #[derive(Component)]
 pub struct HomeMenuUIComponent;
 
 impl UIComponent for HomeMenuUIComponent {
-    type Params = Res<'static, AssetServer>;
+    type Params = (Res<'static, AssetServer>, Res<'static, Profile>);
 
     fn build_ui(self, builder: &mut ChildBuilder<'_>, params: &StaticSystemParam<Self::Params>) {
         const WIDTH_PX: f32 = 500.;
         const HEIGHT_PX: f32 = 600.;
 
+        let (asset_server, profile) = &**params;
+
         builder
             .spawn(Node {
                 left: Val::Percent(50.),
@@ -40,26 +42,232 @@ impl UIComponent for HomeMenuUIComponent {
             })
             .with_children(|builder| {
                 builder.spawn(super::image_bundle(
-                    params.load("septagon.png"),
+                    asset_server.load("septagon.png"),
                     Node {
                         width: Val::Px(WIDTH_PX),
                         height: Val::Px(WIDTH_PX),
                         ..Default::default()
                     },
                 ));
+                ClassSelector::spawn(builder, profile.player_class);
+                if *profile.max_ascension > 0 {
+                    AscensionSelector::spawn(builder, profile.ascension);
+                }
                 builder
                     .spawn(super::text_bundle(
                         "Play",
                         TextFont::default(),
                         Node::default(),
                     ))
-                    .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
-                        let local_player = commands.spawn(super::LocalPlayer).id();
-                        commands.trigger(SpawnCampaign {
-                            player: Some(local_player),
-                        });
-                        commands.entity(trigger.entity()).despawn();
-                    });
+                    .observe(
+                        |trigger: Trigger<Pointer<Click>>,
+                         mut commands: Commands,
+                         profile: Res<Profile>| {
+                            let local_player = commands.spawn(super::LocalPlayer).id();
+                            commands.trigger(SpawnCampaign {
+                                player: Some(local_player),
+                                ascension: profile.ascension,
+                                player_class: profile.player_class,
+                                mutators: profile.mutators.clone(),
+                            });
+                            commands.entity(trigger.entity()).despawn();
+                        },
+                    );
+                builder
+                    .spawn(super::text_bundle(
+                        "Hotseat",
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut super::AppScreen>| {
+                            **app_screen = super::AppScreen::HotseatSetup;
+                        },
+                    );
+                #[cfg(not(target_arch = "wasm32"))]
+                builder
+                    .spawn(super::text_bundle(
+                        "Online",
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut super::AppScreen>| {
+                            **app_screen =
+                                super::AppScreen::Online(super::OnlineUIComponent::Lobby);
+                        },
+                    );
+                builder
+                    .spawn(super::text_bundle(
+                        MutatorsUIComponent::button_text(&profile.mutators),
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut super::AppScreen>| {
+                            **app_screen = super::AppScreen::Mutators;
+                        },
+                    );
+                builder
+                    .spawn(super::text_bundle(
+                        "History",
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut super::AppScreen>| {
+                            **app_screen = super::AppScreen::History;
+                        },
+                    );
+                builder
+                    .spawn(super::text_bundle(
+                        "Achievements",
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut super::AppScreen>| {
+                            **app_screen = super::AppScreen::Achievements;
+                        },
+                    );
+            });
+    }
+}
+
+/// Lets the player pick any unlocked ascension tier for their next run.
+#[derive(Component)]
+pub struct AscensionSelector;
+
+impl AscensionSelector {
+    fn spawn(builder: &mut ChildBuilder<'_>, ascension: Ascension) {
+        builder
+            .spawn((
+                AscensionSelector,
+                Node {
+                    column_gap: Val::Px(12.),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+            ))
+            .with_children(|builder| {
+                builder
+                    .spawn(super::text_bundle(
+                        "<",
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(Self::make_on_click(Ascension::previous));
+                builder.spawn((
+                    AscensionLabel,
+                    super::text_bundle(
+                        AscensionLabel::text(ascension),
+                        TextFont::default(),
+                        Node::default(),
+                    ),
+                ));
+                builder
+                    .spawn(super::text_bundle(
+                        ">",
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(Self::make_on_click(Ascension::next));
             });
     }
+
+    #[allow(clippy::type_complexity)]
+    fn make_on_click(
+        change: fn(&Ascension) -> Ascension,
+    ) -> impl FnMut(Trigger<Pointer<Click>>, ResMut<Profile>, Single<&mut Text, With<AscensionLabel>>)
+    {
+        move |_trigger: Trigger<Pointer<Click>>,
+              mut profile: ResMut<Profile>,
+              mut label: Single<&mut Text, With<AscensionLabel>>| {
+            let ascension = change(&profile.ascension);
+            profile.select_ascension(ascension);
+            label.0 = AscensionLabel::text(profile.ascension);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AscensionLabel;
+
+impl AscensionLabel {
+    fn text(ascension: Ascension) -> String {
+        format!(
+            "Ascension {:}: {:}",
+            *ascension,
+            ascension.get_description()
+        )
+    }
+}
+
+/// Cycles through the unlocked classes for the next run.
+#[derive(Component)]
+pub struct ClassSelector;
+
+impl ClassSelector {
+    fn spawn(builder: &mut ChildBuilder<'_>, player_class: PlayerClass) {
+        builder
+            .spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                ClassSelector,
+            ))
+            .with_children(|builder| {
+                builder.spawn((
+                    ClassLabel,
+                    super::text_bundle(
+                        ClassLabel::text(player_class),
+                        TextFont::default(),
+                        Node::default(),
+                    ),
+                ));
+                builder.spawn((
+                    ClassDescription,
+                    super::text_bundle(
+                        player_class.get_description(),
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Node::default(),
+                    ),
+                ));
+            })
+            .observe(Self::on_click);
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn on_click(
+        _trigger: Trigger<Pointer<Click>>,
+        mut profile: ResMut<Profile>,
+        mut label: Single<&mut Text, (With<ClassLabel>, Without<ClassDescription>)>,
+        mut description: Single<&mut Text, (With<ClassDescription>, Without<ClassLabel>)>,
+    ) {
+        profile.cycle_player_class();
+        label.0 = ClassLabel::text(profile.player_class);
+        description.0 = profile.player_class.get_description();
+    }
+}
+
+#[derive(Component)]
+pub struct ClassLabel;
+
+impl ClassLabel {
+    fn text(player_class: PlayerClass) -> String {
+        format!("Class: {player_class}")
+    }
 }
+
+#[derive(Component)]
+pub struct ClassDescription;
@@ -0,0 +1,333 @@
+use bevy::{
+    color::palettes::css,
+    ecs::system::StaticSystemParam,
+    prelude::{
+        AlignItems, App, BuildChildren, Changed, ChildBuild, ChildBuilder, Click, Commands,
+        Component, Entity, FlexDirection, JustifyContent, Node, Plugin, Pointer, Query, Res,
+        ResMut, Single, Text, TextColor, TextFont, Trigger, UiRect, Update, Val, With,
+    },
+};
+
+use crate::{
+    Element, ElementTable, Encounter, Game, GameEnded, GameOver, Hotseat, HotseatSetup,
+    PlayerElements, Round, Seat, SpawnHotseat,
+};
+
+use super::{text_bundle, AppScreen, GameUIComponent, LocalPlayer, UIComponent};
+
+pub struct HotseatUIPlugin;
+
+impl Plugin for HotseatUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, Self::show_turns);
+        app.add_observer(Self::show_result);
+    }
+}
+
+impl HotseatUIPlugin {
+    /// Hides the game behind a pass the device screen whenever the turn changes hands.
+    fn show_turns(
+        games: Query<(Entity, &Hotseat), Changed<Hotseat>>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        for (game, hotseat) in &games {
+            **app_screen = AppScreen::PassDevice(PassDeviceUIComponent {
+                game,
+                seat: hotseat.turn,
+            });
+        }
+    }
+
+    fn show_result(
+        trigger: Trigger<GameEnded>,
+        games: Query<&Game, With<Hotseat>>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        let GameEnded { game, result } = *trigger.event();
+        let Ok(game) = games.get(game) else {
+            return;
+        };
+        let winner = match result {
+            GameOver::Winner(winner) => game.seat(winner),
+            GameOver::Draw => None,
+        };
+        **app_screen = AppScreen::HotseatOver(HotseatOverUIComponent { winner });
+    }
+}
+
+/// Lets both players pick their elements before a hotseat game.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct HotseatSetupUIComponent;
+
+impl UIComponent for HotseatSetupUIComponent {
+    type Params = Res<'static, HotseatSetup>;
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, params: &StaticSystemParam<Self::Params>) {
+        let setup = &**params;
+
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                row_gap: Val::Px(8.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle("Hotseat", TextFont::default(), Node::default()));
+                builder.spawn((
+                    text_bundle(
+                        format!(
+                            "Click an element to add it and cycle its upgrades. Each player needs at least {}.",
+                            PlayerElements::MIN_ELEMENTS
+                        ),
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Node::default(),
+                    ),
+                    TextColor(css::GRAY.into()),
+                ));
+                builder
+                    .spawn(Node {
+                        column_gap: Val::Px(48.),
+                        ..Default::default()
+                    })
+                    .with_children(|builder| {
+                        for seat in [Seat::PlayerOne, Seat::PlayerTwo] {
+                            builder
+                                .spawn(Node {
+                                    row_gap: Val::Px(4.),
+                                    flex_direction: FlexDirection::Column,
+                                    ..Default::default()
+                                })
+                                .with_children(|builder| {
+                                    builder.spawn(text_bundle(
+                                        seat.to_string(),
+                                        TextFont::default(),
+                                        Node::default(),
+                                    ));
+                                    for element in Element::ALL {
+                                        let slot = HotseatElementSlot { seat, element };
+                                        builder
+                                            .spawn((
+                                                slot,
+                                                text_bundle(
+                                                    slot.text(setup.elements(seat)),
+                                                    TextFont::default(),
+                                                    Node::default(),
+                                                ),
+                                            ))
+                                            .observe(HotseatElementSlot::on_click);
+                                    }
+                                });
+                        }
+                    });
+                builder
+                    .spawn(text_bundle(
+                        "Start",
+                        TextFont::default(),
+                        Node {
+                            margin: UiRect::top(Val::Px(16.)),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut commands: Commands,
+                         setup: Res<HotseatSetup>| {
+                            if setup.is_ready() {
+                                commands.trigger(SpawnHotseat::from(&*setup));
+                            }
+                        },
+                    );
+                builder
+                    .spawn(text_bundle("Back", TextFont::default(), Node::default()))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut AppScreen>| {
+                            **app_screen = AppScreen::HomeMenu;
+                        },
+                    );
+            });
+    }
+}
+
+/// One element of one player's setup, cycled by clicking.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct HotseatElementSlot {
+    seat: Seat,
+    element: Element,
+}
+
+impl HotseatElementSlot {
+    fn text(&self, elements: &PlayerElements) -> String {
+        if !elements.elements.contains(&self.element) {
+            return format!("[ ] {}", self.element);
+        }
+        match elements.augmentations.get(&self.element) {
+            Some(augmentation) => format!("[x] {} ({augmentation})", self.element),
+            None => format!("[x] {}", self.element),
+        }
+    }
+
+    fn on_click(
+        trigger: Trigger<Pointer<Click>>,
+        mut setup: ResMut<HotseatSetup>,
+        mut slots: Query<(&HotseatElementSlot, &mut Text)>,
+    ) {
+        let Ok((slot, mut text)) = slots.get_mut(trigger.entity()) else {
+            return;
+        };
+        setup.cycle_element(slot.seat, slot.element);
+        text.0 = slot.text(setup.elements(slot.seat));
+    }
+}
+
+/// Shown between turns so the next player can take the device without seeing the last throw.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct PassDeviceUIComponent {
+    pub game: Entity,
+    /// The player who should take the device.
+    pub seat: Seat,
+}
+
+impl PassDeviceUIComponent {
+    #[allow(clippy::type_complexity)]
+    fn make_on_ready(
+        game: Entity,
+        seat: Seat,
+    ) -> impl FnMut(
+        Trigger<Pointer<Click>>,
+        Commands,
+        Query<(&Game, &ElementTable)>,
+        Query<&PlayerElements>,
+        Single<&mut AppScreen>,
+    ) {
+        move |_trigger: Trigger<Pointer<Click>>,
+              mut commands: Commands,
+              games: Query<(&Game, &ElementTable)>,
+              players: Query<&PlayerElements>,
+              mut app_screen: Single<&mut AppScreen>| {
+            let Ok((game, table)) = games.get(game) else {
+                return;
+            };
+            let (player, opponent) = (game.player(seat), game.player(seat.opponent()));
+            let Ok([player_elements, opponent_elements]) = players.get_many([player, opponent])
+            else {
+                return;
+            };
+            commands.entity(opponent).remove::<LocalPlayer>();
+            commands.entity(player).insert(LocalPlayer);
+            **app_screen = AppScreen::InGame(GameUIComponent {
+                player_elements: player_elements.clone(),
+                enemy_elements: opponent_elements.clone(),
+                // both players chose their elements in the open
+                revealed_enemy_elements: opponent_elements.elements.clone(),
+                relics: Vec::new(),
+                table: table.clone(),
+                round: Round::default(),
+                encounter: Encounter::Battle,
+                progress: None,
+                seat: Some(seat),
+                spectating: false,
+                player,
+            });
+        }
+    }
+}
+
+impl UIComponent for PassDeviceUIComponent {
+    type Params = ();
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, _: &StaticSystemParam<Self::Params>) {
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                row_gap: Val::Px(16.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    format!("Pass the device to {}", self.seat),
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder.spawn((
+                    text_bundle(
+                        format!("{} should look away", self.seat.opponent()),
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Node::default(),
+                    ),
+                    TextColor(css::GRAY.into()),
+                ));
+                builder
+                    .spawn(text_bundle("Ready", TextFont::default(), Node::default()))
+                    .observe(Self::make_on_ready(self.game, self.seat));
+            });
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct HotseatOverUIComponent {
+    /// `None` when the game was drawn.
+    pub winner: Option<Seat>,
+}
+
+impl UIComponent for HotseatOverUIComponent {
+    type Params = ();
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, _: &StaticSystemParam<Self::Params>) {
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                row_gap: Val::Px(16.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    match self.winner {
+                        Some(seat) => format!("{seat} wins!"),
+                        None => "It's a draw!".to_string(),
+                    },
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder
+                    .spawn(text_bundle("Rematch", TextFont::default(), Node::default()))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut commands: Commands,
+                         setup: Res<HotseatSetup>| {
+                            commands.trigger(SpawnHotseat::from(&*setup));
+                        },
+                    );
+                builder
+                    .spawn(text_bundle("Home", TextFont::default(), Node::default()))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut AppScreen>| {
+                            **app_screen = AppScreen::HomeMenu;
+                        },
+                    );
+            });
+    }
+}
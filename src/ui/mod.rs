@@ -14,6 +14,9 @@ use bevy::{
     },
 };
 
+mod achievement;
+pub use achievement::*;
+
 mod bonus;
 pub use bonus::*;
 
@@ -26,9 +29,24 @@ pub use game::*;
 mod game_over;
 pub use game_over::*;
 
+mod history;
+pub use history::*;
+
 mod home;
 pub use home::*;
 
+mod hotseat;
+pub use hotseat::*;
+
+mod mutator;
+pub use mutator::*;
+
+mod online;
+pub use online::*;
+
+mod relic;
+pub use relic::*;
+
 use crate::{Action, Aspect, Augmentation, Element};
 
 pub struct UIPlugin;
@@ -43,10 +61,20 @@ impl Plugin for UIPlugin {
         app.add_systems(Update, Self::watch_screen_changes.in_set(UISystems::Watch));
         app.add_plugins((
             GameUIPlugin,
+            AchievementUIPlugin,
+            HotseatUIPlugin,
+            OnlineUIPlugin,
             UIComponentPlugin::<HomeMenuUIComponent>::default(),
             UIComponentPlugin::<GameUIComponent>::default(),
             UIComponentPlugin::<BonusUIComponent>::default(),
             UIComponentPlugin::<GameOverUIComponent>::default(),
+            UIComponentPlugin::<HistoryUIComponent>::default(),
+            UIComponentPlugin::<AchievementsUIComponent>::default(),
+            UIComponentPlugin::<MutatorsUIComponent>::default(),
+            UIComponentPlugin::<HotseatSetupUIComponent>::default(),
+            UIComponentPlugin::<PassDeviceUIComponent>::default(),
+            UIComponentPlugin::<HotseatOverUIComponent>::default(),
+            UIComponentPlugin::<OnlineUIComponent>::default(),
         ));
     }
 }
@@ -71,7 +99,14 @@ impl UIPlugin {
             .remove::<HomeMenuUIComponent>()
             .remove::<GameUIComponent>()
             .remove::<BonusUIComponent>()
-            .remove::<GameOverUIComponent>();
+            .remove::<GameOverUIComponent>()
+            .remove::<HistoryUIComponent>()
+            .remove::<AchievementsUIComponent>()
+            .remove::<MutatorsUIComponent>()
+            .remove::<HotseatSetupUIComponent>()
+            .remove::<PassDeviceUIComponent>()
+            .remove::<HotseatOverUIComponent>()
+            .remove::<OnlineUIComponent>();
         app_screen.build_ui(&mut commands, root);
     }
 }
@@ -158,14 +193,22 @@ impl<C> Default for UIComponentPlugin<C> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 #[derive(Component)]
 #[require(Node(Self::node))]
 pub enum AppScreen {
+    #[default]
     HomeMenu,
     InGame(GameUIComponent),
     ProvidingBonus(BonusUIComponent),
     GameOver(GameOverUIComponent),
+    History,
+    Achievements,
+    Mutators,
+    HotseatSetup,
+    PassDevice(PassDeviceUIComponent),
+    HotseatOver(HotseatOverUIComponent),
+    Online(OnlineUIComponent),
 }
 
 impl AppScreen {
@@ -194,16 +237,31 @@ impl AppScreen {
             AppScreen::GameOver(ui_child) => {
                 commands.entity(root).insert(ui_child.clone());
             }
+            AppScreen::History => {
+                commands.entity(root).insert(HistoryUIComponent);
+            }
+            AppScreen::Achievements => {
+                commands.entity(root).insert(AchievementsUIComponent);
+            }
+            AppScreen::Mutators => {
+                commands.entity(root).insert(MutatorsUIComponent);
+            }
+            AppScreen::HotseatSetup => {
+                commands.entity(root).insert(HotseatSetupUIComponent);
+            }
+            AppScreen::PassDevice(ui_child) => {
+                commands.entity(root).insert(ui_child.clone());
+            }
+            AppScreen::HotseatOver(ui_child) => {
+                commands.entity(root).insert(ui_child.clone());
+            }
+            AppScreen::Online(ui_child) => {
+                commands.entity(root).insert(ui_child.clone());
+            }
         }
     }
 }
 
-impl Default for AppScreen {
-    fn default() -> Self {
-        Self::HomeMenu
-    }
-}
-
 #[derive(Debug)]
 #[derive(Resource)]
 pub struct RpsGlyphs(BTreeMap<Action, Handle<Image>>);
@@ -0,0 +1,119 @@
+use bevy::{
+    color::palettes::css,
+    ecs::system::StaticSystemParam,
+    prelude::{
+        AlignItems, BuildChildren, ChildBuild, ChildBuilder, Click, Component, FlexDirection,
+        JustifyContent, Node, Pointer, Query, Res, ResMut, Single, Text, TextColor, TextFont,
+        Trigger, UiRect, Val,
+    },
+};
+
+use crate::{Mutator, Mutators, Profile};
+
+use super::{text_bundle, AppScreen, UIComponent};
+
+/// Toggles the mutators used by the next run.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct MutatorsUIComponent;
+
+impl MutatorsUIComponent {
+    /// The label of the home menu button which opens this screen.
+    pub fn button_text(mutators: &Mutators) -> String {
+        if mutators.is_empty() {
+            "Mutators".to_string()
+        } else {
+            format!("Mutators ({} active)", mutators.len())
+        }
+    }
+}
+
+impl UIComponent for MutatorsUIComponent {
+    type Params = Res<'static, Profile>;
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, params: &StaticSystemParam<Self::Params>) {
+        let profile = &**params;
+
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                row_gap: Val::Px(8.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    "Mutators",
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                builder.spawn((
+                    text_bundle(
+                        "Runs with mutators do not count towards leaderboards",
+                        TextFont {
+                            font_size: 14.,
+                            ..Default::default()
+                        },
+                        Node::default(),
+                    ),
+                    TextColor(css::GRAY.into()),
+                ));
+                for mutator in Mutator::ALL {
+                    builder
+                        .spawn((
+                            MutatorToggle(mutator),
+                            text_bundle(
+                                MutatorToggle(mutator).text(&profile.mutators),
+                                TextFont::default(),
+                                Node::default(),
+                            ),
+                        ))
+                        .observe(MutatorToggle::on_click);
+                }
+                builder
+                    .spawn(text_bundle(
+                        "Back",
+                        TextFont::default(),
+                        Node {
+                            margin: UiRect::top(Val::Px(16.)),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         mut app_screen: Single<&mut AppScreen>| {
+                            **app_screen = AppScreen::HomeMenu;
+                        },
+                    );
+            });
+    }
+}
+
+#[derive(Component)]
+pub struct MutatorToggle(Mutator);
+
+impl MutatorToggle {
+    fn text(&self, mutators: &Mutators) -> String {
+        let checkbox = if mutators.contains(&self.0) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        format!("{checkbox} {}: {}", self.0, self.0.get_description())
+    }
+
+    fn on_click(
+        trigger: Trigger<Pointer<Click>>,
+        mut profile: ResMut<Profile>,
+        mut toggles: Query<(&MutatorToggle, &mut Text)>,
+    ) {
+        let Ok((toggle, mut text)) = toggles.get_mut(trigger.entity()) else {
+            return;
+        };
+        profile.mutators.toggle(toggle.0);
+        text.0 = toggle.text(&profile.mutators);
+    }
+}
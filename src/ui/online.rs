@@ -0,0 +1,461 @@
+use bevy::{
+    color::palettes::css,
+    ecs::system::StaticSystemParam,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::{
+        AlignItems, App, BuildChildren, ChildBuild, ChildBuilder, Click, Commands, Component,
+        Entity, EventReader, FlexDirection, JustifyContent, Node, Plugin, Pointer, Query, Res,
+        Single, TextColor, TextFont, Trigger, Update, Val, With,
+    },
+};
+
+use crate::{
+    ElementTable, Encounter, Game, GameEnded, GameOver, Lobby, OnlineClient,
+    OnlineConnectionFailed, OnlineGameStarted, OnlineJoinRejected, OnlineRoomCreated,
+    OnlineSettings, PlayerElements, RemoteGame, RoomCode, Round, Seat,
+};
+
+use super::{text_bundle, AppScreen, GameUIComponent, LocalPlayer, UIComponent};
+
+pub struct OnlineUIPlugin;
+
+impl Plugin for OnlineUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(Self::show_game);
+        app.add_observer(Self::show_room);
+        app.add_observer(Self::show_rejection);
+        app.add_observer(Self::show_connection_failure);
+        app.add_observer(Self::show_result);
+        app.add_systems(Update, Self::type_room_code);
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, Self::reconnect);
+    }
+}
+
+impl OnlineUIPlugin {
+    fn show_game(
+        trigger: Trigger<OnlineGameStarted>,
+        mut commands: Commands,
+        games: Query<(&Game, &ElementTable)>,
+        players: Query<&PlayerElements>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        let OnlineGameStarted { game, seat } = *trigger.event();
+        let Ok((game, table)) = games.get(game) else {
+            return;
+        };
+        // spectators watch from the first player's side
+        let spectating = seat.is_none();
+        let seat = seat.unwrap_or(Seat::PlayerOne);
+        let (player, opponent) = (game.player(seat), game.player(seat.opponent()));
+        let Ok([player_elements, opponent_elements]) = players.get_many([player, opponent]) else {
+            return;
+        };
+        commands.entity(player).insert(LocalPlayer);
+        **app_screen = AppScreen::InGame(GameUIComponent {
+            player_elements: player_elements.clone(),
+            enemy_elements: opponent_elements.clone(),
+            // the server shares both players' elements when the game starts
+            revealed_enemy_elements: opponent_elements.elements.clone(),
+            relics: Vec::new(),
+            table: table.clone(),
+            round: Round::default(),
+            encounter: Encounter::Battle,
+            progress: None,
+            seat: Some(seat),
+            spectating,
+            player,
+        });
+    }
+
+    fn show_room(trigger: Trigger<OnlineRoomCreated>, mut app_screen: Single<&mut AppScreen>) {
+        **app_screen = AppScreen::Online(OnlineUIComponent::Waiting {
+            room: Some(trigger.event().code.clone()),
+        });
+    }
+
+    fn show_rejection(
+        trigger: Trigger<OnlineJoinRejected>,
+        mut commands: Commands,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        let OnlineJoinRejected { client, error } = trigger.event();
+        commands.entity(*client).despawn();
+        **app_screen = AppScreen::Online(OnlineUIComponent::Failed(error.to_string()));
+    }
+
+    /// Keeps trying to rejoin a game lost mid-game, but gives up on a first connection.
+    fn show_connection_failure(
+        trigger: Trigger<OnlineConnectionFailed>,
+        mut commands: Commands,
+        clients: Query<&OnlineClient>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        let OnlineConnectionFailed { client, error } = trigger.event();
+        if clients.get(*client).is_ok_and(OnlineClient::can_rejoin) {
+            bevy::log::info!("Could not rejoin the game yet: {error}");
+            return;
+        }
+        commands.entity(*client).despawn();
+        **app_screen = AppScreen::Online(OnlineUIComponent::Failed(error.to_string()));
+    }
+
+    fn show_result(
+        trigger: Trigger<GameEnded>,
+        games: Query<(&Game, &RemoteGame)>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        let GameEnded {
+            game: game_entity,
+            result,
+        } = *trigger.event();
+        let Ok((game, remote)) = games.get(game_entity) else {
+            return;
+        };
+        let winner = match result {
+            GameOver::Winner(winner) => game.seat(winner),
+            GameOver::Draw => None,
+        };
+        **app_screen = AppScreen::Online(match remote.seat {
+            Some(seat) => OnlineUIComponent::Ended {
+                won: winner.map(|winner| winner == seat),
+            },
+            None => OnlineUIComponent::Watched { winner },
+        });
+    }
+
+    /// Lets the player type a room code on the room code screen.
+    fn type_room_code(
+        mut commands: Commands,
+        mut keys: EventReader<KeyboardInput>,
+        settings: Res<OnlineSettings>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        let AppScreen::Online(OnlineUIComponent::EnterRoomCode(typed)) = &**app_screen else {
+            keys.clear();
+            return;
+        };
+        let mut code = typed.clone();
+        for key in keys.read() {
+            if key.state != ButtonState::Pressed {
+                continue;
+            }
+            match &key.logical_key {
+                Key::Character(text) if code.len() < RoomCode::LENGTH => {
+                    code.extend(text.chars().filter(char::is_ascii_alphabetic));
+                    code.truncate(RoomCode::LENGTH);
+                    code.make_ascii_uppercase();
+                }
+                Key::Backspace => {
+                    code.pop();
+                }
+                Key::Enter => {
+                    if let Some(screen) =
+                        OnlineUIComponent::enter_room(&mut commands, &settings, &code, false)
+                    {
+                        **app_screen = AppScreen::Online(screen);
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if code != *typed {
+            **app_screen = AppScreen::Online(OnlineUIComponent::EnterRoomCode(code));
+        }
+    }
+
+    /// Retries the connection to a game lost mid-game every few seconds, so the player can rejoin.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(clippy::type_complexity)]
+    fn reconnect(
+        mut commands: Commands,
+        clients: Query<
+            (Entity, &OnlineClient),
+            (
+                bevy::prelude::Without<crate::Connection>,
+                bevy::prelude::Without<crate::PendingConnection>,
+            ),
+        >,
+        settings: Res<OnlineSettings>,
+        time: Res<bevy::prelude::Time>,
+        mut next_attempt: bevy::prelude::Local<std::time::Duration>,
+    ) {
+        const RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+        if time.elapsed() < *next_attempt {
+            return;
+        }
+        *next_attempt = time.elapsed() + RECONNECT_INTERVAL;
+        for (client, online_client) in &clients {
+            if online_client.can_rejoin() {
+                commands
+                    .entity(client)
+                    .insert(Self::open_connection(&settings));
+            }
+        }
+    }
+
+    /// Connects `client` to the server in [`OnlineSettings`] without waiting for it to answer.
+    /// A connection which cannot be opened is reported by [`OnlineConnectionFailed`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect(
+        commands: &mut Commands,
+        settings: &OnlineSettings,
+        client: OnlineClient,
+    ) -> OnlineUIComponent {
+        commands.spawn((client, Self::open_connection(settings)));
+        OnlineUIComponent::Waiting { room: None }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_connection(settings: &OnlineSettings) -> crate::PendingConnection {
+        let url = settings.server_url.clone();
+        crate::PendingConnection::new(move || {
+            crate::WebSocketTransport::connect(&url).map(crate::Connection::new)
+        })
+    }
+
+    /// The home menu hides online play in the browser, which has no transport yet.
+    #[cfg(target_arch = "wasm32")]
+    pub fn connect(_: &mut Commands, _: &OnlineSettings, _: OnlineClient) -> OnlineUIComponent {
+        OnlineUIComponent::Failed("Online play is not available in the browser.".to_string())
+    }
+}
+
+/// The online screens shown outside of a game.
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub enum OnlineUIComponent {
+    /// Chooses between a quick match, a private room and watching a game.
+    Lobby,
+    /// The room code typed so far.
+    EnterRoomCode(String),
+    Waiting {
+        /// The private room the player is waiting in, if any.
+        room: Option<RoomCode>,
+    },
+    Failed(String),
+    /// `won` is `None` when the game was drawn.
+    Ended {
+        won: Option<bool>,
+    },
+    /// The end of a game the player was spectating. `winner` is `None` when it was drawn.
+    Watched {
+        winner: Option<Seat>,
+    },
+}
+
+impl OnlineUIComponent {
+    fn text(&self) -> String {
+        match self {
+            OnlineUIComponent::Lobby => "Online".to_string(),
+            OnlineUIComponent::EnterRoomCode(code) => {
+                format!("Room code: {code:_<width$}", width = RoomCode::LENGTH)
+            }
+            OnlineUIComponent::Waiting { room: None } => "Waiting for an opponent...".to_string(),
+            OnlineUIComponent::Waiting { room: Some(code) } => {
+                format!("Room {code} is open. Waiting for an opponent...")
+            }
+            OnlineUIComponent::Failed(error) => format!("Could not connect. {error}"),
+            OnlineUIComponent::Ended { won: Some(true) } => "You win!".to_string(),
+            OnlineUIComponent::Ended { won: Some(false) } => "You lose!".to_string(),
+            OnlineUIComponent::Ended { won: None } => "It's a draw!".to_string(),
+            OnlineUIComponent::Watched { winner: Some(seat) } => format!("{seat} wins!"),
+            OnlineUIComponent::Watched { winner: None } => "It's a draw!".to_string(),
+        }
+    }
+
+    /// Joins or watches the room named by `code`, or `None` if the code could not name a room.
+    fn enter_room(
+        commands: &mut Commands,
+        settings: &OnlineSettings,
+        code: &str,
+        watch: bool,
+    ) -> Option<OnlineUIComponent> {
+        let code = RoomCode::parse(code)?;
+        let client = if watch {
+            OnlineClient::spectator(Some(code))
+        } else {
+            OnlineClient::new(PlayerElements::rock_paper_scissors(), Lobby::JoinRoom(code))
+        };
+        Some(OnlineUIPlugin::connect(commands, settings, client))
+    }
+
+    fn make_on_connect(
+        client: OnlineClient,
+    ) -> impl FnMut(Trigger<Pointer<Click>>, Commands, Res<OnlineSettings>, Single<&mut AppScreen>)
+    {
+        move |_trigger: Trigger<Pointer<Click>>,
+              mut commands: Commands,
+              settings: Res<OnlineSettings>,
+              mut app_screen: Single<&mut AppScreen>| {
+            **app_screen = AppScreen::Online(OnlineUIPlugin::connect(
+                &mut commands,
+                &settings,
+                client.clone(),
+            ));
+        }
+    }
+
+    fn make_on_enter_room(
+        watch: bool,
+    ) -> impl FnMut(Trigger<Pointer<Click>>, Commands, Res<OnlineSettings>, Single<&mut AppScreen>)
+    {
+        move |_trigger: Trigger<Pointer<Click>>,
+              mut commands: Commands,
+              settings: Res<OnlineSettings>,
+              mut app_screen: Single<&mut AppScreen>| {
+            let AppScreen::Online(OnlineUIComponent::EnterRoomCode(code)) = &**app_screen else {
+                return;
+            };
+            if let Some(screen) = Self::enter_room(&mut commands, &settings, code, watch) {
+                **app_screen = AppScreen::Online(screen);
+            }
+        }
+    }
+
+    /// Disconnects from the server and returns to the home menu.
+    fn leave(
+        _trigger: Trigger<Pointer<Click>>,
+        mut commands: Commands,
+        clients: Query<Entity, With<OnlineClient>>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        for client in &clients {
+            commands.entity(client).despawn();
+        }
+        **app_screen = AppScreen::HomeMenu;
+    }
+
+    /// Drops the old client and goes back to the lobby, to be paired with a new opponent.
+    fn play_again(
+        _trigger: Trigger<Pointer<Click>>,
+        mut commands: Commands,
+        clients: Query<Entity, With<OnlineClient>>,
+        mut app_screen: Single<&mut AppScreen>,
+    ) {
+        for client in &clients {
+            commands.entity(client).despawn();
+        }
+        **app_screen = AppScreen::Online(OnlineUIComponent::Lobby);
+    }
+}
+
+impl UIComponent for OnlineUIComponent {
+    type Params = ();
+
+    fn build_ui(self, builder: &mut ChildBuilder<'_>, _: &StaticSystemParam<Self::Params>) {
+        builder
+            .spawn(Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                row_gap: Val::Px(16.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            })
+            .with_children(|builder| {
+                builder.spawn(text_bundle(
+                    self.text(),
+                    TextFont::default(),
+                    Node::default(),
+                ));
+                match &self {
+                    OnlineUIComponent::Lobby => {
+                        for (text, client) in [
+                            (
+                                "Quick match",
+                                OnlineClient::new(
+                                    PlayerElements::rock_paper_scissors(),
+                                    Lobby::QuickMatch,
+                                ),
+                            ),
+                            (
+                                "Create room",
+                                OnlineClient::new(
+                                    PlayerElements::rock_paper_scissors(),
+                                    Lobby::CreateRoom,
+                                ),
+                            ),
+                            ("Watch a game", OnlineClient::spectator(None)),
+                        ] {
+                            builder
+                                .spawn(text_bundle(text, TextFont::default(), Node::default()))
+                                .observe(Self::make_on_connect(client));
+                        }
+                        builder
+                            .spawn(text_bundle(
+                                "Join room",
+                                TextFont::default(),
+                                Node::default(),
+                            ))
+                            .observe(
+                                |_trigger: Trigger<Pointer<Click>>,
+                                 mut app_screen: Single<&mut AppScreen>| {
+                                    **app_screen = AppScreen::Online(
+                                        OnlineUIComponent::EnterRoomCode(String::new()),
+                                    );
+                                },
+                            );
+                    }
+                    OnlineUIComponent::EnterRoomCode(_) => {
+                        builder.spawn((
+                            text_bundle(
+                                "Type the code of a friend's room, then press Enter.",
+                                TextFont {
+                                    font_size: 14.,
+                                    ..Default::default()
+                                },
+                                Node::default(),
+                            ),
+                            TextColor(css::GRAY.into()),
+                        ));
+                        builder
+                            .spawn(text_bundle("Join", TextFont::default(), Node::default()))
+                            .observe(Self::make_on_enter_room(false));
+                        builder
+                            .spawn(text_bundle("Watch", TextFont::default(), Node::default()))
+                            .observe(Self::make_on_enter_room(true));
+                    }
+                    OnlineUIComponent::Waiting { room: Some(_) } => {
+                        builder.spawn((
+                            text_bundle(
+                                "Share the code with a friend so they can join.",
+                                TextFont {
+                                    font_size: 14.,
+                                    ..Default::default()
+                                },
+                                Node::default(),
+                            ),
+                            TextColor(css::GRAY.into()),
+                        ));
+                    }
+                    OnlineUIComponent::Ended { .. } | OnlineUIComponent::Watched { .. } => {
+                        builder
+                            .spawn(text_bundle(
+                                "Play again",
+                                TextFont::default(),
+                                Node::default(),
+                            ))
+                            .observe(Self::play_again);
+                    }
+                    OnlineUIComponent::Waiting { room: None } | OnlineUIComponent::Failed(_) => {}
+                }
+                builder
+                    .spawn(text_bundle(
+                        match self {
+                            OnlineUIComponent::Waiting { .. } => "Cancel",
+                            _ => "Home",
+                        },
+                        TextFont::default(),
+                        Node::default(),
+                    ))
+                    .observe(Self::leave);
+            });
+    }
+}
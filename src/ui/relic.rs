@@ -0,0 +1,100 @@
+use bevy::prelude::{
+    Alpha, BackgroundColor, BuildChildren, ChildBuild, ChildBuilder, Commands, Component,
+    DespawnRecursiveExt, Entity, FlexDirection, FlexWrap, Name, Node, Out, Over, Pointer,
+    PositionType, Query, TextFont, Trigger, UiRect, Val,
+};
+
+use crate::Relic;
+
+#[derive(Component)]
+pub struct RelicsPanel;
+
+impl RelicsPanel {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.),
+            left: Val::Px(20.),
+            right: Val::Auto,
+            bottom: Val::Auto,
+            width: Val::Px(200.),
+            flex_direction: FlexDirection::Column,
+            flex_wrap: FlexWrap::Wrap,
+            row_gap: Val::Px(8.),
+            ..Default::default()
+        }
+    }
+
+    pub fn spawn(builder: &mut ChildBuilder<'_>, relics: &[Relic]) {
+        builder
+            .spawn((RelicsPanel, RelicsPanel::node()))
+            .with_children(|builder| {
+                for relic in relics {
+                    RelicTooltip::spawn_relic(builder, *relic);
+                }
+            });
+    }
+}
+
+#[derive(Component)]
+pub struct RelicTooltip {
+    pub relic_node: Entity,
+}
+
+impl RelicTooltip {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(100.),
+            width: Val::Px(300.),
+            border: UiRect::all(Val::Px(1.)),
+            ..Default::default()
+        }
+    }
+
+    pub fn background_color() -> BackgroundColor {
+        use bevy::color::palettes::css;
+        css::DARK_SLATE_BLUE.with_alpha(0.95).into()
+    }
+
+    pub fn spawn_relic(builder: &mut ChildBuilder<'_>, relic: Relic) {
+        builder
+            .spawn(super::text_bundle(
+                relic.to_string(),
+                TextFont::default(),
+                Node::default(),
+            ))
+            .observe(Self::make_on_over(relic))
+            .observe(Self::make_on_out());
+    }
+
+    pub fn make_on_over(relic: Relic) -> impl FnMut(Trigger<Pointer<Over>>, Commands) {
+        move |trigger: Trigger<Pointer<Over>>, mut commands: Commands| {
+            let tooltip_ui = commands
+                .spawn((
+                    Name::new("Relic Tooltip"),
+                    Self {
+                        relic_node: trigger.entity(),
+                    },
+                    super::text_bundle(relic.get_description(), TextFont::default(), Self::node()),
+                    Self::background_color(),
+                ))
+                .id();
+            commands.entity(trigger.entity()).add_child(tooltip_ui);
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn make_on_out(
+    ) -> impl FnMut(Trigger<Pointer<Out>>, Commands, Query<(Entity, &RelicTooltip)>) {
+        move |trigger: Trigger<Pointer<Out>>,
+              mut commands: Commands,
+              tooltips: Query<(Entity, &RelicTooltip)>| {
+            for (panel, RelicTooltip { relic_node }) in &tooltips {
+                if *relic_node == trigger.entity() {
+                    commands.entity(panel).despawn_recursive();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use bevy_prng::WyRand;
+use rand::SeedableRng;
+
+use rock_paper_paradise::{
+    BanishedBonuses, BonusKind, BonusPity, BonusPool, Level, PlayerElements, Rarity, RarityTier,
+};
+
+const PITY_THRESHOLD: u32 = 4;
+const ROLLS: usize = 500;
+
+/// A pool whose common tier is rolled almost every time, unless bad-luck protection steps in.
+fn pool(rare_weights: BTreeMap<Level, u32>) -> BonusPool {
+    BonusPool {
+        pity_threshold: PITY_THRESHOLD,
+        tiers: vec![
+            RarityTier {
+                rarity: Rarity::Common,
+                weights: BTreeMap::from([(Level::Three, 1_000_000)]),
+                bonuses: vec![(BonusKind::AttackPlus, 1)],
+            },
+            RarityTier {
+                rarity: Rarity::Rare,
+                weights: rare_weights,
+                bonuses: vec![(BonusKind::DoubleDown, 1)],
+            },
+        ],
+    }
+}
+
+fn roll_rarities(pool: &BonusPool, level: Level) -> Vec<(u32, Rarity)> {
+    let mut rng = WyRand::seed_from_u64(0);
+    let mut pity = BonusPity::default();
+    (0..ROLLS)
+        .map(|_| {
+            let pity_before = *pity;
+            let offer = pool
+                .roll(
+                    level,
+                    &PlayerElements::rock_paper_scissors(),
+                    &BanishedBonuses::default(),
+                    &mut pity,
+                    &mut rng,
+                )
+                .expect("the common tier should always offer a bonus");
+            (pity_before, offer.rarity)
+        })
+        .collect()
+}
+
+#[test]
+fn rares_are_guaranteed_after_enough_other_bonuses() {
+    let pool = pool(BTreeMap::from([(Level::Three, 1)]));
+
+    let rolls = roll_rarities(&pool, Level::Three);
+    for (pity, rarity) in &rolls {
+        assert!(*pity <= PITY_THRESHOLD);
+        if *pity == PITY_THRESHOLD {
+            assert_eq!(*rarity, Rarity::Rare);
+        }
+    }
+    let rares = rolls
+        .iter()
+        .filter(|(_, rarity)| *rarity == Rarity::Rare)
+        .count();
+    assert!(rares >= ROLLS / (PITY_THRESHOLD as usize + 1));
+}
+
+#[test]
+fn tiers_weighted_zero_are_never_rolled() {
+    let pool = pool(BTreeMap::from([(Level::Three, 1), (Level::Four, 0)]));
+
+    // not even bad-luck protection brings back a tier turned off at this level
+    let rolls = roll_rarities(&pool, Level::Four);
+    assert!(rolls.iter().all(|(_, rarity)| *rarity == Rarity::Common));
+    assert!(rolls.iter().any(|(pity, _)| *pity > PITY_THRESHOLD));
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::{App, Entity, MinimalPlugins};
+use bevy_prng::WyRand;
+use bevy_rand::plugin::EntropyPlugin;
+
+use rock_paper_paradise::{Combo, Element, GamePlugin, Guess, Health, PlayerElements, SpawnGame};
+
+fn game() -> (App, [Entity; 2]) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+    ));
+    let world = app.world_mut();
+    let players = [(); 2].map(|_| {
+        world
+            .spawn((PlayerElements::rock_paper_scissors(), Health::new(10)))
+            .id()
+    });
+    world.trigger(SpawnGame {
+        player_one: Some(players[0]),
+        player_two: Some(players[1]),
+        ..Default::default()
+    });
+    app.update();
+    (app, players)
+}
+
+fn throw(app: &mut App, [one, two]: [Entity; 2], guesses: [Element; 2]) {
+    app.world_mut()
+        .entity_mut(one)
+        .insert(Guess::new(guesses[0]));
+    app.world_mut()
+        .entity_mut(two)
+        .insert(Guess::new(guesses[1]));
+    app.update();
+}
+
+fn has_combo(app: &App, player: Entity) -> bool {
+    app.world().get::<Combo>(player).is_some()
+}
+
+#[test]
+fn combo_lasts_until_a_throw_is_lost() {
+    let (mut app, players) = game();
+    let [one, two] = players;
+
+    throw(&mut app, players, [Element::Rock, Element::Scissors]);
+    assert!(has_combo(&app, one));
+    assert!(!has_combo(&app, two));
+
+    // draws continue the combo
+    throw(&mut app, players, [Element::Paper, Element::Paper]);
+    assert!(has_combo(&app, one));
+
+    throw(&mut app, players, [Element::Scissors, Element::Rock]);
+    assert!(!has_combo(&app, one));
+    assert!(has_combo(&app, two));
+}
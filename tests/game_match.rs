@@ -0,0 +1,179 @@
+use bevy::prelude::{App, Entity, MinimalPlugins, With};
+use bevy_prng::WyRand;
+use bevy_rand::plugin::EntropyPlugin;
+
+use rock_paper_paradise::{
+    Combo, Element, Game, GameOver, GamePlugin, GameRules, Guess, Health, Match, MatchPlugin,
+    MatchRules, PlayerElements, Seat, SpawnGame, SpawnMatch,
+};
+
+const STARTING_HEALTH: u32 = 2;
+
+/// Starts a match between two players who each need to lose two throws to lose a game.
+/// Throwing the same element deals 1 damage to both, so two in a row draw the game.
+fn start_match(rules: MatchRules) -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+        MatchPlugin,
+    ));
+    let world = app.world_mut();
+    let [player_one, player_two] = [(); 2].map(|_| {
+        world
+            .spawn((
+                PlayerElements::rock_paper_scissors(),
+                Health::new(STARTING_HEALTH),
+            ))
+            .id()
+    });
+    world.trigger(SpawnMatch {
+        player_one: Some(player_one),
+        player_two: Some(player_two),
+        rules,
+        game: SpawnGame {
+            rules: Some(GameRules {
+                draw_damage: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    });
+    app.update();
+    (app, player_one, player_two)
+}
+
+fn game_match(app: &mut App) -> &Match {
+    let world = app.world_mut();
+    world.query::<&Match>().single(world)
+}
+
+fn match_result(app: &mut App) -> Option<GameOver> {
+    let world = app.world_mut();
+    world
+        .query_filtered::<Option<&GameOver>, With<Match>>()
+        .single(world)
+        .copied()
+}
+
+fn throw(app: &mut App, one: Element, two: Element) {
+    let game = game_match(app)
+        .current_game
+        .expect("the match should be in progress");
+    let world = app.world_mut();
+    let Game {
+        player_one,
+        player_two,
+    } = *world.get::<Game>(game).unwrap();
+    world.entity_mut(player_one).insert(Guess::new(one));
+    world.entity_mut(player_two).insert(Guess::new(two));
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn win_game(app: &mut App, winner: Seat) {
+    let throws = match winner {
+        Seat::PlayerOne => (Element::Rock, Element::Scissors),
+        Seat::PlayerTwo => (Element::Scissors, Element::Rock),
+    };
+    for _ in 0..STARTING_HEALTH {
+        throw(app, throws.0, throws.1);
+    }
+}
+
+fn draw_game(app: &mut App) {
+    for _ in 0..STARTING_HEALTH {
+        throw(app, Element::Paper, Element::Paper);
+    }
+}
+
+#[test]
+fn matches_count_wins_for_each_player() {
+    let (mut app, _, two) = start_match(MatchRules::default());
+
+    win_game(&mut app, Seat::PlayerOne);
+    assert_eq!(game_match(&mut app).wins, (1, 0));
+    win_game(&mut app, Seat::PlayerTwo);
+    assert_eq!(game_match(&mut app).wins, (1, 1));
+    assert!(match_result(&mut app).is_none());
+
+    win_game(&mut app, Seat::PlayerTwo);
+    let game_match = game_match(&mut app);
+    assert_eq!(game_match.wins, (1, 2));
+    assert_eq!(game_match.games_played(), 3);
+    assert!(game_match.current_game.is_none());
+    assert!(matches!(match_result(&mut app), Some(GameOver::Winner(winner)) if winner == two));
+}
+
+#[test]
+fn matches_end_once_a_player_wins_a_majority() {
+    let (mut app, one, _) = start_match(MatchRules {
+        best_of: 5,
+        ..Default::default()
+    });
+
+    for _ in 0..2 {
+        win_game(&mut app, Seat::PlayerOne);
+        assert!(match_result(&mut app).is_none());
+    }
+    win_game(&mut app, Seat::PlayerOne);
+    assert_eq!(game_match(&mut app).wins, (3, 0));
+    assert!(matches!(match_result(&mut app), Some(GameOver::Winner(winner)) if winner == one));
+}
+
+#[test]
+fn players_are_reset_between_games() {
+    let (mut app, one, two) = start_match(MatchRules::default());
+    let first_game = game_match(&mut app).current_game.unwrap();
+
+    win_game(&mut app, Seat::PlayerOne);
+    let second_game = game_match(&mut app).current_game.unwrap();
+    assert_ne!(first_game, second_game);
+    assert!(app.world().get_entity(first_game).is_err());
+    for player in [one, two] {
+        assert_eq!(
+            **app.world().get::<Health>(player).unwrap(),
+            STARTING_HEALTH
+        );
+    }
+    // winning a throw gains Combo, which is cleared for the next game
+    assert!(app.world().get::<Combo>(one).is_none());
+}
+
+#[test]
+fn players_keep_combo_between_games_unless_reset() {
+    let (mut app, one, _) = start_match(MatchRules {
+        reset_combo: false,
+        ..Default::default()
+    });
+
+    win_game(&mut app, Seat::PlayerOne);
+    assert!(app.world().get::<Combo>(one).is_some());
+}
+
+#[test]
+fn matches_end_after_too_many_draws() {
+    let (mut app, ..) = start_match(MatchRules {
+        max_draws: 2,
+        ..Default::default()
+    });
+
+    draw_game(&mut app);
+    assert_eq!(game_match(&mut app).draws, 1);
+    assert!(match_result(&mut app).is_none());
+    draw_game(&mut app);
+    assert!(matches!(match_result(&mut app), Some(GameOver::Draw)));
+
+    let (mut app, one_ahead, _) = start_match(MatchRules {
+        max_draws: 1,
+        ..Default::default()
+    });
+    win_game(&mut app, Seat::PlayerOne);
+    draw_game(&mut app);
+    assert_eq!(game_match(&mut app).wins, (1, 0));
+    assert!(
+        matches!(match_result(&mut app), Some(GameOver::Winner(winner)) if winner == one_ahead)
+    );
+}
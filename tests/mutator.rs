@@ -0,0 +1,54 @@
+use bevy::prelude::{App, MinimalPlugins};
+use bevy_prng::WyRand;
+use bevy_rand::plugin::EntropyPlugin;
+
+use rock_paper_paradise::{
+    Element, GamePlugin, Guess, Health, Mutator, Mutators, PlayerElements, RoundHistory, SpawnGame,
+};
+
+const ROUNDS: usize = 100;
+
+#[test]
+fn random_aspects_never_match_the_thrown_element() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+    ));
+    let world = app.world_mut();
+    let players = [(); 2].map(|_| {
+        world
+            .spawn((PlayerElements::rock_paper_scissors(), Health::new(1000)))
+            .id()
+    });
+    let game = world.spawn_empty().id();
+    world.trigger(SpawnGame {
+        game: Some(game),
+        player_one: Some(players[0]),
+        player_two: Some(players[1]),
+        mutators: Some(Mutators([Mutator::RandomAspects].into())),
+        ..Default::default()
+    });
+    app.update();
+
+    for round in 0..ROUNDS {
+        let guess = [Element::Rock, Element::Paper, Element::Scissors][round % 3];
+        for player in players {
+            app.world_mut().entity_mut(player).insert(Guess::new(guess));
+        }
+        app.update();
+    }
+
+    let history = app.world().get::<RoundHistory>(game).unwrap();
+    assert_eq!(history.len(), ROUNDS);
+    for record in history.iter() {
+        for side in [&record.player_one, &record.player_two] {
+            let aspect = side
+                .action
+                .enchantment
+                .expect("every throw should gain an Aspect");
+            assert_ne!(*aspect, side.action.guess);
+        }
+    }
+}
@@ -0,0 +1,583 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bevy::prelude::{App, Bundle, Entity, MinimalPlugins, ResMut, Resource, Trigger, With};
+use bevy_prng::WyRand;
+use bevy_rand::plugin::EntropyPlugin;
+
+use rock_paper_paradise::{
+    decode, encode, ClientMessage, Commitment, Connection, DisconnectGracePeriod, Element, Game,
+    GameEnded, GameOver, GamePlugin, GameRules, Guess, Health, HostedGameRules, Lobby,
+    LocalTransport, OnlineClient, OnlineClientPlugin, OnlineServerPlugin, PendingConnection,
+    PlayerElements, ProtocolError, RemoteGame, RemotePlayer, RoundHistory, Seat, ServerMessage,
+    Transport, WebSocketListener, WebSocketTransport,
+};
+
+fn server() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+        OnlineServerPlugin,
+    ));
+    app
+}
+
+fn client(transport: impl Transport, online_client: OnlineClient) -> App {
+    spawn_client((Connection::new(transport), online_client))
+}
+
+/// A client which connects to `url` on another thread, as the game does.
+fn websocket_client(url: &str, online_client: OnlineClient) -> App {
+    let url = url.to_string();
+    spawn_client((
+        PendingConnection::new(move || WebSocketTransport::connect(&url).map(Connection::new)),
+        online_client,
+    ))
+}
+
+fn spawn_client(client: impl Bundle) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+        OnlineClientPlugin,
+    ));
+    app.world_mut().spawn(client);
+    app
+}
+
+fn player(lobby: Lobby) -> OnlineClient {
+    OnlineClient::new(PlayerElements::rock_paper_scissors(), lobby)
+}
+
+fn connect(server: &mut App) -> LocalTransport {
+    let (client_end, server_end) = LocalTransport::pair();
+    server
+        .world_mut()
+        .spawn((Connection::new(server_end), RemotePlayer::default()));
+    client_end
+}
+
+fn send(transport: &mut LocalTransport, message: &ClientMessage) {
+    transport.send(encode(message)).unwrap();
+}
+
+fn join(transport: &mut LocalTransport, lobby: Lobby) {
+    send(
+        transport,
+        &ClientMessage::Join {
+            elements: PlayerElements::rock_paper_scissors(),
+            lobby,
+        },
+    );
+}
+
+/// Updates clients talking to a server on another thread until every client is `done`.
+fn update_until(apps: &mut [&mut App], mut done: impl FnMut(&mut App) -> bool) {
+    for _ in 0..1000 {
+        if apps.iter_mut().all(|app| done(app)) {
+            return;
+        }
+        for app in apps.iter_mut() {
+            app.update();
+        }
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    panic!("the server did not answer in time");
+}
+
+/// Every message the server has sent through `transport` so far.
+fn received(transport: &mut LocalTransport) -> Vec<ServerMessage> {
+    std::iter::from_fn(|| transport.receive().unwrap())
+        .map(|text| decode::<ServerMessage>(&text).unwrap())
+        .collect()
+}
+
+fn update_all(apps: &mut [&mut App]) {
+    for _ in 0..4 {
+        for app in apps.iter_mut() {
+            app.update();
+        }
+    }
+}
+
+/// The local player and opponent in a client's mirrored game.
+fn players(client: &mut App) -> (Entity, Entity) {
+    try_players(client).expect("the client should be in a game")
+}
+
+fn try_players(client: &mut App) -> Option<(Entity, Entity)> {
+    let world = client.world_mut();
+    let (game, remote) = world
+        .query::<(&Game, &RemoteGame)>()
+        .get_single(world)
+        .ok()?;
+    let seat = remote.seat?;
+    Some((game.player(seat), game.player(seat.opponent())))
+}
+
+#[test]
+fn rounds_are_resolved_by_the_server() {
+    let mut server = server();
+    let mut one = client(connect(&mut server), player(Lobby::QuickMatch));
+    let mut two = client(connect(&mut server), player(Lobby::QuickMatch));
+    update_all(&mut [&mut server, &mut one, &mut two]);
+
+    let (one_player, _) = players(&mut one);
+    let (two_player, two_opponent) = players(&mut two);
+    one.world_mut()
+        .entity_mut(one_player)
+        .insert(Guess::new(Element::Rock));
+    update_all(&mut [&mut server, &mut one, &mut two]);
+    // nothing is resolved until both players have thrown
+    assert!(two
+        .world_mut()
+        .query::<&RoundHistory>()
+        .single(two.world())
+        .is_empty());
+
+    two.world_mut()
+        .entity_mut(two_player)
+        .insert(Guess::new(Element::Scissors));
+    update_all(&mut [&mut server, &mut one, &mut two]);
+
+    for client in [&mut one, &mut two] {
+        let world = client.world_mut();
+        let (game, history) = world.query::<(&Game, &RoundHistory)>().single(world);
+        assert_eq!(history.len(), 1);
+        let record = &history[0];
+        assert_eq!(record.player_one.action.guess, Element::Rock);
+        assert_eq!(record.player_two.action.guess, Element::Scissors);
+        let winner = game.player(Seat::PlayerOne);
+        let loser = game.player(Seat::PlayerTwo);
+        assert!(**world.get::<Health>(winner).unwrap() > **world.get::<Health>(loser).unwrap());
+    }
+    assert!(
+        **two.world().get::<Health>(two_player).unwrap()
+            < **two.world().get::<Health>(two_opponent).unwrap()
+    );
+}
+
+#[derive(Default)]
+#[derive(Resource)]
+struct EndedGames(Vec<GameOver>);
+
+#[test]
+fn games_are_ended_by_the_server() {
+    let mut server = server();
+    server.insert_resource(HostedGameRules(GameRules {
+        starting_health: (Some(1), Some(1)),
+        ..Default::default()
+    }));
+    let mut one = client(connect(&mut server), player(Lobby::QuickMatch));
+    let mut two = client(connect(&mut server), player(Lobby::QuickMatch));
+    for client in [&mut one, &mut two] {
+        client.init_resource::<EndedGames>();
+        client.add_observer(
+            |trigger: Trigger<GameEnded>, mut ended_games: ResMut<EndedGames>| {
+                ended_games.0.push(trigger.event().result);
+            },
+        );
+    }
+    update_all(&mut [&mut server, &mut one, &mut two]);
+
+    let (one_player, _) = players(&mut one);
+    let (two_player, two_opponent) = players(&mut two);
+    one.world_mut()
+        .entity_mut(one_player)
+        .insert(Guess::new(Element::Rock));
+    two.world_mut()
+        .entity_mut(two_player)
+        .insert(Guess::new(Element::Scissors));
+    update_all(&mut [&mut server, &mut one, &mut two]);
+
+    for (client, winner) in [(&mut one, one_player), (&mut two, two_opponent)] {
+        let ended_games = &client.world().resource::<EndedGames>().0;
+        assert!(matches!(ended_games.as_slice(), [GameOver::Winner(won)] if *won == winner));
+        let world = client.world_mut();
+        assert!(world.query::<&Game>().iter(world).next().is_none());
+        // a finished game can't be rejoined
+        assert!(!world.query::<&OnlineClient>().single(world).can_rejoin());
+    }
+}
+
+#[test]
+fn reveals_must_match_commitments() {
+    let mut server = server();
+    let mut cheater = connect(&mut server);
+    let mut other = client(connect(&mut server), player(Lobby::QuickMatch));
+    join(&mut cheater, Lobby::QuickMatch);
+    update_all(&mut [&mut server, &mut other]);
+
+    let (other_player, _) = players(&mut other);
+    other
+        .world_mut()
+        .entity_mut(other_player)
+        .insert(Guess::new(Element::Paper));
+    send(
+        &mut cheater,
+        &ClientMessage::Commit {
+            round: 0,
+            commitment: Commitment::new(0, Element::Rock, 7),
+        },
+    );
+    update_all(&mut [&mut server, &mut other]);
+    // revealing a different throw than the one committed to is rejected
+    send(
+        &mut cheater,
+        &ClientMessage::Reveal {
+            round: 0,
+            guess: Element::Scissors,
+            nonce: 7,
+        },
+    );
+    update_all(&mut [&mut server, &mut other]);
+
+    let messages = received(&mut cheater);
+    assert!(messages
+        .iter()
+        .any(|message| matches!(message, ServerMessage::RevealRequested { round: 0 })));
+    assert!(messages.iter().any(|message| matches!(
+        message,
+        ServerMessage::Rejected {
+            error: ProtocolError::CommitmentMismatch
+        }
+    )));
+    assert!(!messages
+        .iter()
+        .any(|message| matches!(message, ServerMessage::RoundResolved { .. })));
+}
+
+#[test]
+fn rooms_pair_players_by_code() {
+    let mut server = server();
+    let mut host = connect(&mut server);
+    let mut stranger = client(connect(&mut server), player(Lobby::QuickMatch));
+    join(&mut host, Lobby::CreateRoom);
+    update_all(&mut [&mut server, &mut stranger]);
+
+    let messages = received(&mut host);
+    let [ServerMessage::RoomCreated { code }] = messages.as_slice() else {
+        panic!("the host should only be told the room code, got {messages:?}");
+    };
+    // quick matches never join a room
+    assert!(try_players(&mut stranger).is_none());
+
+    let mut friend = client(connect(&mut server), player(Lobby::JoinRoom(code.clone())));
+    update_all(&mut [&mut server, &mut stranger, &mut friend]);
+    assert!(try_players(&mut friend).is_some());
+    assert!(try_players(&mut stranger).is_none());
+    assert!(received(&mut host)
+        .iter()
+        .any(|message| matches!(message, ServerMessage::Started { .. })));
+
+    let mut latecomer = connect(&mut server);
+    join(&mut latecomer, Lobby::JoinRoom(code.clone()));
+    update_all(&mut [&mut server]);
+    assert!(received(&mut latecomer).iter().any(|message| matches!(
+        message,
+        ServerMessage::Rejected {
+            error: ProtocolError::RoomNotFound(_)
+        }
+    )));
+}
+
+#[test]
+fn quick_matches_pair_players_in_the_order_they_joined() {
+    let mut server = server();
+    let mut hosts = [(); 2].map(|_| connect(&mut server));
+    for host in &mut hosts {
+        join(host, Lobby::CreateRoom);
+    }
+    let mut first = connect(&mut server);
+    join(&mut first, Lobby::QuickMatch);
+    update_all(&mut [&mut server]);
+    assert!(received(&mut first).is_empty());
+
+    let mut second = connect(&mut server);
+    let mut third = connect(&mut server);
+    join(&mut second, Lobby::QuickMatch);
+    join(&mut third, Lobby::QuickMatch);
+    // the hosts leaving shuffles the players the server stores after them
+    drop(hosts);
+    update_all(&mut [&mut server]);
+    let is_started = |messages: &[ServerMessage]| {
+        messages
+            .iter()
+            .any(|message| matches!(message, ServerMessage::Started { .. }))
+    };
+    assert!(is_started(&received(&mut first)));
+    // both newcomers joined after the first player, so only one of them was paired
+    let newcomers = [received(&mut second), received(&mut third)];
+    assert_eq!(
+        newcomers
+            .iter()
+            .filter(|messages| is_started(messages))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn players_can_rejoin_after_disconnecting() {
+    let mut server = server();
+    let mut one = client(connect(&mut server), player(Lobby::QuickMatch));
+    let mut two = client(connect(&mut server), player(Lobby::QuickMatch));
+    update_all(&mut [&mut server, &mut one, &mut two]);
+    let throw = |client: &mut App, element| {
+        let (player, _) = players(client);
+        client
+            .world_mut()
+            .entity_mut(player)
+            .insert(Guess::new(element));
+    };
+    throw(&mut one, Element::Rock);
+    throw(&mut two, Element::Scissors);
+    update_all(&mut [&mut server, &mut one, &mut two]);
+
+    let one_client = one
+        .world_mut()
+        .query_filtered::<Entity, With<OnlineClient>>()
+        .single(one.world());
+    one.world_mut()
+        .entity_mut(one_client)
+        .remove::<Connection>();
+    update_all(&mut [&mut server, &mut one, &mut two]);
+    let transport = connect(&mut server);
+    one.world_mut()
+        .entity_mut(one_client)
+        .insert(Connection::new(transport));
+    update_all(&mut [&mut server, &mut one, &mut two]);
+
+    // the rejoined client has a fresh copy of the game, caught up with the round already played
+    let (one_player, one_opponent) = players(&mut one);
+    {
+        let world = one.world_mut();
+        let history = world.query::<&RoundHistory>().single(world);
+        assert_eq!(history.len(), 1);
+        assert!(
+            **world.get::<Health>(one_player).unwrap()
+                > **world.get::<Health>(one_opponent).unwrap()
+        );
+    }
+
+    throw(&mut one, Element::Paper);
+    throw(&mut two, Element::Rock);
+    update_all(&mut [&mut server, &mut one, &mut two]);
+    for client in [&mut one, &mut two] {
+        let world = client.world_mut();
+        let history = world.query::<&RoundHistory>().single(world);
+        assert_eq!(history.len(), 2);
+    }
+}
+
+#[test]
+fn disconnected_players_forfeit_after_the_grace_period() {
+    let mut server = server();
+    server.insert_resource(DisconnectGracePeriod(Duration::ZERO));
+    let mut one = connect(&mut server);
+    let mut two = connect(&mut server);
+    join(&mut one, Lobby::QuickMatch);
+    join(&mut two, Lobby::QuickMatch);
+    update_all(&mut [&mut server]);
+    let Some(seat) = received(&mut two).iter().find_map(|message| match message {
+        ServerMessage::Started { seat, .. } => Some(*seat),
+        _ => None,
+    }) else {
+        panic!("the game should have started");
+    };
+
+    drop(one);
+    update_all(&mut [&mut server]);
+    let messages = received(&mut two);
+    assert!(messages
+        .iter()
+        .any(|message| matches!(message, ServerMessage::OpponentDisconnected)));
+    assert!(messages.iter().any(
+        |message| matches!(message, ServerMessage::GameEnded { winner } if *winner == Some(seat))
+    ));
+}
+
+#[test]
+fn games_can_be_played_over_websockets() {
+    let listener = WebSocketListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let is_running = Arc::new(AtomicBool::new(true));
+    let server_thread = std::thread::spawn({
+        let is_running = Arc::clone(&is_running);
+        move || {
+            let mut server = server();
+            server.insert_resource(listener);
+            while is_running.load(Ordering::Relaxed) {
+                server.update();
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    });
+
+    let mut one = websocket_client(&url, player(Lobby::QuickMatch));
+    let mut two = websocket_client(&url, player(Lobby::QuickMatch));
+    update_until(&mut [&mut one, &mut two], |app| try_players(app).is_some());
+
+    for (client, element) in [(&mut one, Element::Paper), (&mut two, Element::Rock)] {
+        let (player, _) = players(client);
+        client
+            .world_mut()
+            .entity_mut(player)
+            .insert(Guess::new(element));
+    }
+    update_until(&mut [&mut one, &mut two], |app| {
+        let world = app.world_mut();
+        world.query::<&RoundHistory>().single(world).len() == 1
+    });
+
+    is_running.store(false, Ordering::Relaxed);
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn spectators_only_see_revealed_throws() {
+    let mut server = server();
+    let mut one = connect(&mut server);
+    let mut two = connect(&mut server);
+    join(&mut one, Lobby::QuickMatch);
+    join(&mut two, Lobby::QuickMatch);
+    update_all(&mut [&mut server]);
+
+    let mut spectator = connect(&mut server);
+    send(&mut spectator, &ClientMessage::Spectate { code: None });
+    for player in [&mut one, &mut two] {
+        send(
+            player,
+            &ClientMessage::Commit {
+                round: 0,
+                commitment: Commitment::new(0, Element::Rock, 1),
+            },
+        );
+    }
+    update_all(&mut [&mut server]);
+    // commitments and reveal requests are only for the players
+    let messages = received(&mut spectator);
+    let [ServerMessage::Spectating { history, .. }] = messages.as_slice() else {
+        panic!("the spectator should only be sent the game, got {messages:?}");
+    };
+    assert!(history.is_empty());
+
+    send(
+        &mut spectator,
+        &ClientMessage::Reveal {
+            round: 0,
+            guess: Element::Rock,
+            nonce: 1,
+        },
+    );
+    update_all(&mut [&mut server]);
+    assert!(matches!(
+        received(&mut spectator).as_slice(),
+        [ServerMessage::Rejected {
+            error: ProtocolError::Spectator
+        }]
+    ));
+
+    for player in [&mut one, &mut two] {
+        send(
+            player,
+            &ClientMessage::Reveal {
+                round: 0,
+                guess: Element::Rock,
+                nonce: 1,
+            },
+        );
+    }
+    update_all(&mut [&mut server]);
+    let messages = received(&mut spectator);
+    let [ServerMessage::RoundResolved { record }] = messages.as_slice() else {
+        panic!("the spectator should be sent the resolved round, got {messages:?}");
+    };
+    assert_eq!(record.player_one.action.guess, Element::Rock);
+    assert_eq!(record.player_two.action.guess, Element::Rock);
+}
+
+#[test]
+fn spectators_follow_games_in_rooms() {
+    let mut server = server();
+    let mut host = connect(&mut server);
+    join(&mut host, Lobby::CreateRoom);
+    update_all(&mut [&mut server]);
+    let Some(code) = received(&mut host)
+        .into_iter()
+        .find_map(|message| match message {
+            ServerMessage::RoomCreated { code } => Some(code),
+            _ => None,
+        })
+    else {
+        panic!("the room should have been created");
+    };
+    let mut guest = client(connect(&mut server), player(Lobby::JoinRoom(code.clone())));
+    update_all(&mut [&mut server, &mut guest]);
+
+    // games in rooms are private unless the code is known
+    let mut stranger = connect(&mut server);
+    send(&mut stranger, &ClientMessage::Spectate { code: None });
+    update_all(&mut [&mut server]);
+    assert!(matches!(
+        received(&mut stranger).as_slice(),
+        [ServerMessage::Rejected {
+            error: ProtocolError::GameNotFound
+        }]
+    ));
+
+    let (guest_player, _) = players(&mut guest);
+    guest
+        .world_mut()
+        .entity_mut(guest_player)
+        .insert(Guess::new(Element::Paper));
+    update_all(&mut [&mut server, &mut guest]);
+    let mut spectator = client(connect(&mut server), OnlineClient::spectator(Some(code)));
+    update_all(&mut [&mut server, &mut guest, &mut spectator]);
+    // the spectator cannot throw for either player
+    assert!(try_players(&mut spectator).is_none());
+
+    let Some(guest_seat) = ({
+        let world = guest.world_mut();
+        world.query::<&RemoteGame>().single(world).seat
+    }) else {
+        panic!("the guest should have a seat");
+    };
+    let host_seat = guest_seat.opponent();
+    send(
+        &mut host,
+        &ClientMessage::Commit {
+            round: 0,
+            commitment: Commitment::new(0, Element::Rock, 3),
+        },
+    );
+    update_all(&mut [&mut server, &mut guest, &mut spectator]);
+    send(
+        &mut host,
+        &ClientMessage::Reveal {
+            round: 0,
+            guess: Element::Rock,
+            nonce: 3,
+        },
+    );
+    update_all(&mut [&mut server, &mut guest, &mut spectator]);
+
+    let world = spectator.world_mut();
+    let (game, history) = world.query::<(&Game, &RoundHistory)>().single(world);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].side(host_seat).action.guess, Element::Rock);
+    assert_eq!(history[0].side(guest_seat).action.guess, Element::Paper);
+    let (host_player, guest_player) = (game.player(host_seat), game.player(guest_seat));
+    assert!(
+        **world.get::<Health>(guest_player).unwrap() > **world.get::<Health>(host_player).unwrap()
+    );
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::{App, Entity, MinimalPlugins},
+    time::TimeUpdateStrategy,
+};
+use bevy_prng::WyRand;
+use bevy_rand::plugin::EntropyPlugin;
+
+use rock_paper_paradise::{
+    AutoThrow, Element, GamePlugin, GameRules, Guess, Health, PlayerElements, RoundHistory,
+    SpawnGame, ThrowTimer, ThrowTimerRules,
+};
+
+/// The time which passes with each update.
+const FRAME: Duration = Duration::from_millis(100);
+/// The number of updates it takes for a throw to time out.
+const FRAMES_TO_THROW: usize = 10;
+
+/// Spawns a game whose throws time out after one second, on a clock which only moves by [`FRAME`]
+/// with each update.
+fn timed_game(on_timeout: AutoThrow) -> (App, Entity, [Entity; 2]) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        EntropyPlugin::<WyRand>::default(),
+        GamePlugin,
+    ));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    let world = app.world_mut();
+    let players = [(); 2].map(|_| {
+        world
+            .spawn((PlayerElements::rock_paper_scissors(), Health::new(10)))
+            .id()
+    });
+    let game = world.spawn_empty().id();
+    world.trigger(SpawnGame {
+        game: Some(game),
+        player_one: Some(players[0]),
+        player_two: Some(players[1]),
+        rules: Some(GameRules {
+            throw_timer: Some(ThrowTimerRules {
+                seconds: FRAME.as_secs_f32() * FRAMES_TO_THROW as f32,
+                on_timeout,
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    // the clock starts on the first update
+    app.update();
+    (app, game, players)
+}
+
+fn advance(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+fn history(app: &App, game: Entity) -> &RoundHistory {
+    app.world().get::<RoundHistory>(game).unwrap()
+}
+
+fn throw_timer(app: &App, game: Entity) -> &ThrowTimer {
+    app.world().get::<ThrowTimer>(game).unwrap()
+}
+
+#[test]
+fn players_who_run_out_of_time_throw_randomly() {
+    let (mut app, game, [one, _]) = timed_game(AutoThrow::Random);
+    app.world_mut()
+        .entity_mut(one)
+        .insert(Guess::new(Element::Rock));
+
+    advance(&mut app, FRAMES_TO_THROW - 1);
+    assert!(history(&app, game).is_empty());
+    advance(&mut app, 1);
+    let history = history(&app, game);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].player_one.action.guess, Element::Rock);
+    assert!(PlayerElements::rock_paper_scissors()
+        .elements
+        .contains(&history[0].player_two.action.guess));
+}
+
+#[test]
+fn players_who_run_out_of_time_repeat_their_last_throw() {
+    let (mut app, game, [one, two]) = timed_game(AutoThrow::RepeatLast);
+    app.world_mut()
+        .entity_mut(one)
+        .insert(Guess::new(Element::Paper));
+    app.world_mut()
+        .entity_mut(two)
+        .insert(Guess::new(Element::Scissors));
+    advance(&mut app, 1);
+    assert_eq!(history(&app, game).len(), 1);
+
+    advance(&mut app, FRAMES_TO_THROW);
+    let history = history(&app, game);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].player_one.action.guess, Element::Paper);
+    assert_eq!(history[1].player_two.action.guess, Element::Scissors);
+}
+
+#[test]
+fn players_who_run_out_of_time_take_a_penalty() {
+    let (mut app, game, _) = timed_game(AutoThrow::Penalty(2));
+
+    advance(&mut app, FRAMES_TO_THROW);
+    let history = history(&app, game);
+    assert_eq!(history.len(), 1);
+    // a throw deals at most 1 damage, so each player took the penalty on top of it
+    assert!(history[0].player_one.damage >= 2);
+    assert!(history[0].player_two.damage >= 2);
+}
+
+#[test]
+fn the_throw_timer_restarts_each_round() {
+    let (mut app, game, [one, two]) = timed_game(AutoThrow::Random);
+
+    // a round played halfway through the countdown gives the next round the full time again
+    advance(&mut app, FRAMES_TO_THROW / 2);
+    app.world_mut()
+        .entity_mut(one)
+        .insert(Guess::new(Element::Rock));
+    app.world_mut()
+        .entity_mut(two)
+        .insert(Guess::new(Element::Rock));
+    advance(&mut app, 1);
+    assert_eq!(history(&app, game).len(), 1);
+    assert_eq!(throw_timer(&app, game).timer.elapsed(), Duration::ZERO);
+
+    advance(&mut app, FRAMES_TO_THROW - 1);
+    assert_eq!(history(&app, game).len(), 1);
+    advance(&mut app, 1);
+    assert_eq!(history(&app, game).len(), 2);
+    assert_eq!(throw_timer(&app, game).timer.elapsed(), Duration::ZERO);
+}